@@ -0,0 +1,3 @@
+fn main() {
+	http_server::embed_directory("public", "public.rs").unwrap();
+}
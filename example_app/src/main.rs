@@ -1,15 +1,22 @@
-use http_server::{HttpServer, HttpResponse};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use http_server::{HttpMethod, HttpServer, HttpResponse};
+
+struct AppState {
+	visits: AtomicUsize
+}
 
 fn main() {
-	let mut server = HttpServer::new();
+	let mut server = HttpServer::with_state(AppState { visits: AtomicUsize::new(0) });
 
 	server.get(
 		String::from("/"),
 		|request| {
+			let visits = request.state().visits.fetch_add(1, Ordering::Relaxed) + 1;
+
 			HttpResponse::new()
 				.status(200)
-				.header(String::from("Content-Type"), String::from("text/html"))
-				.content(format!("Hello, World! From {}", request.headers.get("Host").unwrap()))
+				.html(format!("Hello, World! From {} (visit {})", request.headers.get("Host").unwrap(), visits))
 		}
 	);
 
@@ -24,17 +31,55 @@ fn main() {
 	);
 
 
+	// A `*` within a segment doesn't cross a `/`, so documents in subdirectories are matched with a catch-all instead
+	server.get(
+		String::from("/documents/*path"),
+		|request| {
+			if !request.params["path"].ends_with(".txt") {
+				return HttpResponse::new().status(404);
+			}
+
+			HttpResponse::new()
+				.status(200)
+				.text(request.uri.path)
+		}
+	);
+
+	server.get(
+		String::from("/users/:id/posts/:post_id"),
+		|request| {
+			HttpResponse::new()
+				.status(200)
+				.text(format!("Post {} by user {}", request.params["post_id"], request.params["id"]))
+		}
+	);
+
+	server.delete(
+		String::from("/users/:id"),
+		|request| {
+			HttpResponse::new()
+				.status(200)
+				.text(format!("Deleted user {}", request.params["id"]))
+		}
+	);
+
+	server.route(
+		HttpMethod::Extension(String::from("PURGE")),
+		String::from("/cache/*"),
+		|_request| HttpResponse::new().status(204)
+	);
+
 	server.get(
-		String::from("/documents/*.txt"),
+		String::from("/files/*rest"),
 		|request| {
 			HttpResponse::new()
 				.status(200)
-				.header(String::from("Content-Type"), String::from("text/plain"))
-				.content(format!("{}", request.uri.path))
+				.text(format!("File {}", request.params["rest"]))
 		}
 	);
 
-	server.serve_static(String::from("/workspaces/http-server/example_app/public/"));
+	// Embedded by build.rs so that the binary doesn't depend on where the public directory is
+	server.serve_embedded(http_server::include_embedded!("public.rs"));
 
 	server.post(String::from("/upload"), |request| {
 		println!("{:#?}", request.headers);
@@ -46,5 +91,5 @@ fn main() {
 			.content(String::from("File uploaded successfully"))
 	});
 
-	server.listen(8080);
+	server.listen(8080).unwrap();
 }
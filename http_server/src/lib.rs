@@ -1,13 +1,21 @@
 mod request;
 mod response;
 mod route;
+mod router;
 mod server;
 mod utils;
 mod method;
 mod mime_type;
+mod options;
+mod path_pattern;
+mod shutdown;
+mod static_files;
 
 pub use method::HttpMethod;
 pub use request::HttpRequest;
 pub use response::HttpResponse;
 pub use route::{HttpRoute, HttpRouteCallback};
-pub use server::HttpServer;
\ No newline at end of file
+pub use server::{BoundHttpServer, HttpServer};
+pub use options::HttpServerOptions;
+pub use shutdown::ShutdownHandle;
+pub use static_files::{embed_directory, DotfilePolicy, EmbeddedFile, EntityTagMode, StaticOptions};
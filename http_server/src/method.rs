@@ -4,17 +4,37 @@ use std::fmt::{
 	Result as FormatResult
 };
 
-#[derive(PartialEq, Eq)]
+use crate::utils;
+
+/// Request methods defined by RFC 9110 section 9, plus any other method token a client sends (e.g. WebDAV's PROPFIND)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum HttpMethod {
 	GET,
+	HEAD,
 	POST,
+	PUT,
+	DELETE,
+	CONNECT,
+	OPTIONS,
+	TRACE,
+	PATCH,
+	Extension(String),
 }
 
 impl HttpMethod {
+	/// Parses a method token. Methods are case-sensitive, so e.g. "get" is an extension method rather than GET.
 	pub fn from_string(string: String) -> Result<Self, String> {
 		return match string.as_str() {
 			"GET" => Ok(HttpMethod::GET),
+			"HEAD" => Ok(HttpMethod::HEAD),
 			"POST" => Ok(HttpMethod::POST),
+			"PUT" => Ok(HttpMethod::PUT),
+			"DELETE" => Ok(HttpMethod::DELETE),
+			"CONNECT" => Ok(HttpMethod::CONNECT),
+			"OPTIONS" => Ok(HttpMethod::OPTIONS),
+			"TRACE" => Ok(HttpMethod::TRACE),
+			"PATCH" => Ok(HttpMethod::PATCH),
+			_ if utils::is_token(&string) => Ok(HttpMethod::Extension(string)),
 			_ => Err(format!("Unrecognised HTTP method {}", string))
 		};
 	}
@@ -24,9 +44,17 @@ impl Display for HttpMethod {
 	fn fmt(&self, f: &mut Formatter) -> FormatResult {
 		let stringified = match self {
 			HttpMethod::GET => "GET",
-			HttpMethod::POST => "POST"
+			HttpMethod::HEAD => "HEAD",
+			HttpMethod::POST => "POST",
+			HttpMethod::PUT => "PUT",
+			HttpMethod::DELETE => "DELETE",
+			HttpMethod::CONNECT => "CONNECT",
+			HttpMethod::OPTIONS => "OPTIONS",
+			HttpMethod::TRACE => "TRACE",
+			HttpMethod::PATCH => "PATCH",
+			HttpMethod::Extension(method) => method
 		};
 
 		return write!(f, "{}", stringified);
 	}
-}
\ No newline at end of file
+}
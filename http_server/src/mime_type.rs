@@ -1,22 +1,212 @@
+use std::collections::HashMap;
+
+/// Built-in extension table. Textual types carry a charset since browsers otherwise guess one.
+const EXTENSION_MIME_TYPES: &[(&str, &str)] = &[
+    // Text
+    ("html", "text/html; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("css", "text/css; charset=utf-8"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("cjs", "text/javascript; charset=utf-8"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("text", "text/plain; charset=utf-8"),
+    ("log", "text/plain; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("tsv", "text/tab-separated-values; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("markdown", "text/markdown; charset=utf-8"),
+    ("ics", "text/calendar; charset=utf-8"),
+    ("vtt", "text/vtt; charset=utf-8"),
+
+    // Structured data
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("jsonld", "application/ld+json"),
+    ("webmanifest", "application/manifest+json"),
+    ("xml", "application/xml"),
+    ("xhtml", "application/xhtml+xml"),
+    ("rss", "application/rss+xml"),
+    ("atom", "application/atom+xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("toml", "application/toml"),
+    ("wasm", "application/wasm"),
+
+    // Images
+    ("png", "image/png"),
+    ("apng", "image/apng"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("jfif", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("svg", "image/svg+xml"),
+    ("ico", "image/x-icon"),
+    ("bmp", "image/bmp"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("heic", "image/heic"),
+    ("heif", "image/heif"),
+
+    // Fonts
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("eot", "application/vnd.ms-fontobject"),
+
+    // Audio
+    ("mp3", "audio/mpeg"),
+    ("m4a", "audio/mp4"),
+    ("aac", "audio/aac"),
+    ("wav", "audio/wav"),
+    ("oga", "audio/ogg"),
+    ("ogg", "audio/ogg"),
+    ("opus", "audio/opus"),
+    ("flac", "audio/flac"),
+    ("weba", "audio/webm"),
+    ("mid", "audio/midi"),
+    ("midi", "audio/midi"),
+
+    // Video
+    ("mp4", "video/mp4"),
+    ("m4v", "video/mp4"),
+    ("webm", "video/webm"),
+    ("ogv", "video/ogg"),
+    ("mov", "video/quicktime"),
+    ("avi", "video/x-msvideo"),
+    ("mpeg", "video/mpeg"),
+    ("mpg", "video/mpeg"),
+    ("mkv", "video/x-matroska"),
+
+    // Documents and archives
+    ("pdf", "application/pdf"),
+    ("rtf", "application/rtf"),
+    ("epub", "application/epub+zip"),
+    ("doc", "application/msword"),
+    ("docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+    ("xls", "application/vnd.ms-excel"),
+    ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ("ppt", "application/vnd.ms-powerpoint"),
+    ("pptx", "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("ods", "application/vnd.oasis.opendocument.spreadsheet"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("tar", "application/x-tar"),
+    ("bz2", "application/x-bzip2"),
+    ("xz", "application/x-xz"),
+    ("7z", "application/x-7z-compressed"),
+    ("rar", "application/vnd.rar"),
+];
+
+/// Magic numbers at the start of a file and the type they identify, for files whose extension is unknown
+const MAGIC_NUMBERS: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+    (b"wOFF", "font/woff"),
+    (b"wOF2", "font/woff2"),
+    (b"OTTO", "font/otf"),
+    (b"\x00\x01\x00\x00", "font/ttf"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"\x00asm", "application/wasm"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+    (b"OggS", "audio/ogg"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+];
+
+pub const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
 pub struct MimeType {}
 
 impl MimeType {
-    pub fn from_file_path(file_path: String) -> String {
-        let file_name = match file_path.contains("/") {
-            true => file_path.split("/").last().unwrap(),
-            false => &file_path
+    /// Looks up the built-in table, ignoring the extension's case
+    pub fn from_extension(extension: &str) -> Option<&'static str> {
+        return EXTENSION_MIME_TYPES
+            .iter()
+            .find(|(known_extension, _mime_type)| known_extension.eq_ignore_ascii_case(extension))
+            .map(|(_extension, mime_type)| *mime_type);
+    }
+
+    /// Guesses the type of some content from its first few bytes
+    pub fn sniff(content: &[u8]) -> Option<&'static str> {
+        if let Some((_magic_number, mime_type)) = MAGIC_NUMBERS.iter().find(|(magic_number, _mime_type)| content.starts_with(magic_number)) {
+            return Some(mime_type);
+        }
+
+        // RIFF and ISO base media containers put the actual format after a size field
+        match (content.get(0..4), content.get(4..8), content.get(8..12)) {
+            (Some(b"RIFF"), _, Some(b"WEBP")) => return Some("image/webp"),
+            (Some(b"RIFF"), _, Some(b"WAVE")) => return Some("audio/wav"),
+            (Some(b"RIFF"), _, Some(b"AVI ")) => return Some("video/x-msvideo"),
+            (_, Some(b"ftyp"), Some(b"avif")) => return Some("image/avif"),
+            (_, Some(b"ftyp"), Some(b"qt  ")) => return Some("video/quicktime"),
+            (_, Some(b"ftyp"), _) => return Some("video/mp4"),
+            _ => ()
         };
 
-        let extension = match file_name.contains(".") {
-            true => file_name.split(".").last().unwrap(),
-            false => ""
+        let text = match core::str::from_utf8(content) {
+            Ok(text) => text,
+            // The sample may have cut a multi-byte character in half
+            Err(error) if error.error_len().is_none() => core::str::from_utf8(&content[..error.valid_up_to()]).unwrap(),
+            Err(_error) => return None
         };
 
-        return match extension {
-            "txt" => String::from("text/plain",),
-            "html" => String::from("text/html"),
+        let lowercase_start = text.trim_start().chars().take(16).collect::<String>().to_ascii_lowercase();
+
+        if lowercase_start.starts_with("<!doctype html") || lowercase_start.starts_with("<html") {
+            return Some("text/html; charset=utf-8");
+        }
+
+        if lowercase_start.starts_with("<?xml") {
+            return Some("application/xml");
+        }
+
+        // Anything else that is valid UTF-8 without binary control characters is treated as text
+        if text.chars().all(|char| !char.is_control() || char.is_ascii_whitespace()) {
+            return Some("text/plain; charset=utf-8");
+        }
+
+        return None;
+    }
+
+    fn extension(file_path: &str) -> &str {
+        let file_name = file_path.rsplit(['/', '\\']).next().unwrap_or(file_path);
+
+        return match file_name.rsplit_once('.') {
+            Some((_stem, extension)) => extension,
+            None => ""
+        };
+    }
+}
+
+/// MIME types registered by the user for extensions, consulted before the built-in table
+#[derive(Default)]
+pub struct MimeTypeOverrides {
+    overrides: HashMap<String, String>
+}
+
+impl MimeTypeOverrides {
+    pub fn insert(&mut self, extension: String, mime_type: String) -> () {
+        self.overrides.insert(extension.trim_start_matches('.').to_ascii_lowercase(), mime_type);
+    }
+
+    /// The MIME type for a file path if either an override or the built-in table knows its extension
+    pub fn lookup(&self, file_path: &str) -> Option<String> {
+        let extension = MimeType::extension(file_path).to_ascii_lowercase();
 
-            _ => String::from("application/octet-stream")
+        return match self.overrides.get(&extension) {
+            Some(mime_type) => Some(mime_type.clone()),
+            None => MimeType::from_extension(&extension).map(String::from)
         };
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,61 @@
+use std::{
+	thread,
+	time::Duration
+};
+
+pub struct HttpServerOptions {
+	/// Number of worker threads handling accepted connections. Connections spend most of their time waiting on IO so this
+	/// defaults to several threads per available core
+	pub worker_threads: usize,
+
+	/// Number of accepted connections that may wait for a free worker before new connections are rejected with a 503
+	pub queue_depth: usize,
+
+	/// How long a persistent connection may sit idle waiting for its next request before it is closed
+	pub keep_alive_timeout: Duration,
+
+	/// Maximum number of requests served over a single connection before it is closed. A value of 1 disables persistent
+	/// connections
+	pub max_requests_per_connection: usize,
+
+	/// Time allowed for a client to send the request line and headers, measured from when the connection is accepted
+	/// or, for subsequent requests on a persistent connection, from the first byte of the request
+	pub header_read_timeout: Duration,
+
+	/// Time allowed for a client to send the request body once the headers have been read
+	pub body_read_timeout: Duration,
+
+	/// Longest request line accepted before responding with 414 URI Too Long
+	pub max_request_line_length: usize,
+
+	/// Largest total size of the request headers accepted before responding with 431 Request Header Fields Too Large
+	pub max_header_size: usize,
+
+	/// Largest number of request headers accepted before responding with 431 Request Header Fields Too Large
+	pub max_header_count: usize,
+
+	/// Largest request body accepted before responding with 413 Content Too Large. Individual routes can override this
+	/// with `HttpRoute::max_body_size`
+	pub max_body_size: usize,
+
+	/// Maximum time a single write of the response may block, e.g. because the client has stopped reading
+	pub write_timeout: Duration,
+}
+
+impl Default for HttpServerOptions {
+	fn default() -> Self {
+		return Self {
+			worker_threads: thread::available_parallelism().map(|count| count.get()).unwrap_or(1) * 4,
+			queue_depth: 128,
+			keep_alive_timeout: Duration::from_secs(5),
+			max_requests_per_connection: 100,
+			header_read_timeout: Duration::from_secs(10),
+			body_read_timeout: Duration::from_secs(30),
+			write_timeout: Duration::from_secs(30),
+			max_request_line_length: 8 * 1024,
+			max_header_size: 16 * 1024,
+			max_header_count: 100,
+			max_body_size: 10 * 1024 * 1024,
+		};
+	}
+}
@@ -0,0 +1,122 @@
+use crate::utils::URLEncoding;
+
+/// One `/` separated segment of a route's path pattern
+pub enum PatternSegment {
+	/// Matches a segment exactly
+	Literal(String),
+	/// Matches a segment where each `*` stands for any run of characters within it, e.g. `*.txt`
+	Glob(String),
+	/// `:name` - matches any non-empty segment and captures it. A bare `*` segment before the end of a pattern matches
+	/// exactly the same segments, so it is one of these without a name.
+	Param(Option<String>),
+	/// `*name` (or a bare `*`) at the end of a pattern - matches the rest of the path, slashes included, capturing it if
+	/// it is named
+	CatchAll(Option<String>)
+}
+
+/// A route's path pattern such as `/users/:id/posts/:post_id` or `/files/*rest`, split into segments once when the route
+/// is registered rather than on every request
+pub struct PathPattern {
+	segments: Vec<PatternSegment>
+}
+
+impl PathPattern {
+	pub fn parse(pattern: &str) -> Self {
+		let raw_segments = Self::split(pattern).collect::<Vec<&str>>();
+		let last_index = raw_segments.len().saturating_sub(1);
+
+		let segments = raw_segments
+			.iter()
+			.enumerate()
+			.map(|(index, segment)| {
+				if let Some(name) = segment.strip_prefix(':').filter(|name| !name.is_empty()) {
+					return PatternSegment::Param(Some(String::from(name)));
+				}
+
+				let is_catch_all_name = |name: &&str| name.chars().all(|char| char.is_alphanumeric() || char == '_');
+
+				if let Some(name) = segment.strip_prefix('*').filter(|name| index == last_index && is_catch_all_name(name)) {
+					return match name.is_empty() {
+						true => PatternSegment::CatchAll(Option::None),
+						false => PatternSegment::CatchAll(Some(String::from(name)))
+					};
+				}
+
+				if *segment == "*" {
+					return PatternSegment::Param(Option::None);
+				}
+
+				return match segment.contains('*') {
+					true => PatternSegment::Glob(String::from(*segment)),
+					false => PatternSegment::Literal(String::from(*segment))
+				};
+			})
+			.collect();
+
+		return Self { segments };
+	}
+
+	/// Splits a path into its segments, ignoring the leading slash, so that "/" is a single empty segment
+	pub fn split(path: &str) -> impl Iterator<Item = &str> {
+		return path.strip_prefix('/').unwrap_or(path).split('/');
+	}
+
+	pub fn segments(&self) -> &[PatternSegment] {
+		return &self.segments;
+	}
+
+	/// Names for the values the pattern captures, in the order they appear in the path. An unnamed `*` segment or
+	/// catch-all still captures a value but it isn't given to the callback.
+	pub fn capture_names(&self) -> Vec<Option<String>> {
+		return self.segments
+			.iter()
+			.filter_map(|segment| match segment {
+				PatternSegment::Param(name) => Some(name.clone()),
+				PatternSegment::CatchAll(name) => Some(name.clone()),
+				_ => Option::None
+			})
+			.collect();
+	}
+
+	/// Matches text against a pattern in which `*` stands for any run of characters, backtracking to the most recent `*`
+	/// on a mismatch so that it runs in linear time for typical patterns. Comparing bytes rather than characters is
+	/// equivalent since UTF-8 never encodes one character as part of another.
+	pub fn glob_matches(glob: &str, text: &str) -> bool {
+		let (glob, text) = (glob.as_bytes(), text.as_bytes());
+
+		let (mut glob_index, mut text_index) = (0, 0);
+		let mut backtrack: Option<(usize, usize)> = Option::None;
+
+		while text_index < text.len() {
+			match glob.get(glob_index) {
+				Some(b'*') => {
+					backtrack = Some((glob_index, text_index));
+					glob_index += 1;
+				},
+				Some(byte) if *byte == text[text_index] => {
+					glob_index += 1;
+					text_index += 1;
+				},
+				_ => match backtrack {
+					// Let the last `*` swallow one more character and try again from there
+					Some((star_index, star_text_index)) => {
+						backtrack = Some((star_index, star_text_index + 1));
+						glob_index = star_index + 1;
+						text_index = star_text_index + 1;
+					},
+					None => return false
+				}
+			}
+		}
+
+		return glob[glob_index..].iter().all(|byte| *byte == b'*');
+	}
+
+	/// Percent-decodes a captured value, keeping it as it was sent if it doesn't decode to valid UTF-8
+	pub fn decode(value: &str) -> String {
+		return match URLEncoding::percent_decode(value).map(String::from_utf8) {
+			Some(Ok(decoded)) => decoded,
+			_ => String::from(value)
+		};
+	}
+}
@@ -30,6 +30,15 @@ impl HttpHeaderValue {
 		return Self(value);
 	}
 
+	/// Splits a comma separated list header (e.g. `Connection: keep-alive, Upgrade`) into its trimmed, non-empty elements
+	pub fn as_list(&self) -> Vec<String> {
+		return self.0
+			.split(',')
+			.map(|element| element.trim().to_string())
+			.filter(|element| !element.is_empty())
+			.collect();
+	}
+
 	pub fn as_parameterized_header_value(&self) -> ParameterizedHeaderValue {
 		let mut scanner = VecScanner::new(self.0.chars().collect::<Vec<char>>());
 
@@ -53,8 +62,8 @@ impl HttpHeaderValue {
 			entry_scanner.consume_exact(1);
 			let mut value = entry_scanner.consume_rest().into_iter().collect::<String>().trim().to_string();
 
-			// Remove enclosing quotes from value if present
-			if value.starts_with("\"") && value.ends_with("\"") {
+			// Remove enclosing quotes from value if present. A lone quote both starts and ends the value but encloses nothing.
+			if value.len() >= 2 && value.starts_with("\"") && value.ends_with("\"") {
 				value = value[1..value.len() - 1].to_string();
 			}
 
@@ -74,6 +83,20 @@ pub trait HttpHeaderParser {
 	fn from_string(header: String) -> Self;
 }
 
+pub trait HttpHeaderLookup {
+	/// Header field names are case-insensitive (RFC 9110 section 5.1) so lookups must not depend on the client's casing
+	fn get_ignore_case(&self, key: &str) -> Option<&HttpHeaderValue>;
+}
+
+impl HttpHeaderLookup for HashMap<String, HttpHeaderValue> {
+	fn get_ignore_case(&self, key: &str) -> Option<&HttpHeaderValue> {
+		return self
+			.iter()
+			.find(|(header_key, _value)| header_key.eq_ignore_ascii_case(key))
+			.map(|(_key, value)| value);
+	}
+}
+
 impl HttpHeaderParser for HashMap<String, HttpHeaderValue> {
 	fn from_string(header: String) -> Self {
 		let mut headers: HttpHeaders = HashMap::new();
@@ -92,7 +115,14 @@ impl HttpHeaderParser for HashMap<String, HttpHeaderValue> {
 			line_scanner.consume_exact(1);
 			let value = line_scanner.consume_rest().into_iter().collect::<String>().trim().to_string();
 
-			headers.insert(key, HttpHeaderValue::new(value));
+			// A field sent on several lines is equivalent to one line with the values joined by commas (RFC 9110 section
+			// 5.3). Keeping the lines apart would leave lookups to pick one of them at random, whichever casing they use.
+			match headers.iter_mut().find(|(existing_key, _value)| existing_key.eq_ignore_ascii_case(&key)) {
+				Some((_key, existing)) => *existing = HttpHeaderValue::new(format!("{}, {}", existing, value)),
+				None => {
+					headers.insert(key, HttpHeaderValue::new(value));
+				}
+			};
 		}
 
 		return headers;
@@ -3,4 +3,5 @@ mod headers;
 mod uploaded_data;
 mod uri;
 
-pub use request::HttpRequest;
\ No newline at end of file
+pub use request::{HttpRequest, HttpRequestParseError};
+pub use headers::HttpHeaderValue;
\ No newline at end of file
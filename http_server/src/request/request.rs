@@ -1,86 +1,308 @@
-use std::io::{BufRead, BufReader, Read};
+use std::{collections::HashMap, io::{BufRead, Error as IoError, ErrorKind, Read}, sync::Arc};
 
 use super::{
-	headers::{HttpHeaderParser, HttpHeaderValue, HttpHeaders}, uploaded_data::UploadedData, uri::URI
+	headers::{HttpHeaderLookup, HttpHeaderParser, HttpHeaderValue, HttpHeaders}, uploaded_data::UploadedData, uri::URI
 };
 
-use crate::HttpMethod;
+use crate::{utils, HttpMethod, HttpServerOptions};
 
 #[derive(Debug)]
 pub enum HttpRequestParseError {
 	UnrecognisedHttpMethod,
 	MalformedHeader,
 	MalformedRequestLine,
-	UnsupportedVersion
+	UnsupportedVersion,
+	/// The client closed the connection before sending a request line
+	ConnectionClosed,
+	/// The client didn't send the request within the time allowed
+	TimedOut,
+	RequestLineTooLong,
+	HeadersTooLarge,
+	BodyTooLarge,
+	MalformedChunk,
+	/// A transfer coding other than chunked was applied to the body
+	UnsupportedTransferCoding,
+	Io(IoError)
 }
 
-pub struct HttpRequest {
+impl From<IoError> for HttpRequestParseError {
+	fn from(error: IoError) -> Self {
+		return match error.kind() {
+			ErrorKind::TimedOut | ErrorKind::WouldBlock => HttpRequestParseError::TimedOut,
+			_ => HttpRequestParseError::Io(error)
+		};
+	}
+}
+
+/// A request received by the server. `S` is the type of the server's application state, which the request carries by
+/// the time it reaches a route callback.
+pub struct HttpRequest<S = ()> {
 	pub method: HttpMethod,
 	pub uri: URI,
 	pub headers: HttpHeaders,
-	pub body: Option<UploadedData>
+	pub body: Option<UploadedData>,
+
+	/// Trailer fields sent after a chunked request body
+	pub trailers: HttpHeaders,
+
+	/// Values captured by the matching route's path pattern, e.g. "id" for `/users/:id`
+	pub params: HashMap<String, String>,
+
+	state: Arc<S>
 }
 
-impl HttpRequest {
-    pub fn from_stream(stream: &mut std::net::TcpStream) -> Result<Self, HttpRequestParseError> {
-        let mut buffer_reader = BufReader::new(stream);
+impl<S> HttpRequest<S> {
+	/// The server's application state, shared by every request
+	pub fn state(&self) -> &S {
+		return &self.state;
+	}
+
+	/// Looks up a request header, ignoring the casing used by the client
+	pub fn header(&self, name: &str) -> Option<&HttpHeaderValue> {
+		return self.headers.get_ignore_case(name);
+	}
 
-        let mut request_line = String::new();
-        buffer_reader.read_line(&mut request_line).unwrap();
+	/// HTTP/1.1 connections are persistent unless either side sends the "close" connection option (RFC 9112 section 9.3)
+	pub fn wants_keep_alive(&self) -> bool {
+		return match self.header("Connection") {
+			Some(connection) => !connection.as_list().iter().any(|option| option.eq_ignore_ascii_case("close")),
+			None => true
+		};
+	}
+
+	/// Attaches the server's application state before the request is handed to a route callback
+	pub(crate) fn with_state<T>(self, state: Arc<T>) -> HttpRequest<T> {
+		return HttpRequest {
+			method: self.method,
+			uri: self.uri,
+			headers: self.headers,
+			body: self.body,
+			trailers: self.trailers,
+			params: self.params,
+			state
+		};
+	}
+}
 
-        let (method, uri, version) = match Self::deserialize_request_line(request_line) {
-            Ok((method, url, version)) => (method, url, version),
+/// Longest chunk size line (including any chunk extensions) accepted in a chunked request body
+const MAX_CHUNK_SIZE_LINE_LENGTH: usize = 4096;
+
+impl HttpRequest {
+	/// Reads the request line and headers. The body is left on the stream until `read_body` is called so that the caller
+	/// can decide how to read it (e.g. with a different timeout) based on the headers.
+	pub fn from_stream(buffer_reader: &mut impl BufRead, options: &HttpServerOptions) -> Result<Self, HttpRequestParseError> {
+		let request_line = match Self::read_line_limited(buffer_reader, options.max_request_line_length)? {
+			Some(request_line) if request_line.is_empty() => return Err(HttpRequestParseError::ConnectionClosed),
+			Some(request_line) => request_line,
+			None => return Err(HttpRequestParseError::RequestLineTooLong)
+		};
+
+		let (method, uri, version) = match Self::deserialize_request_line(request_line) {
+			Ok((method, url, version)) => (method, url, version),
 			Err(e) => return Err(e)
-        };
+		};
+
+		if version.as_str() != "HTTP/1.1" {
+			return Err(HttpRequestParseError::UnsupportedVersion);
+		}
+
+		let headers = Self::read_header_section(buffer_reader, options)?;
+
+		return Ok(Self {
+			method,
+			uri,
+			headers,
+			body: Option::None,
+			trailers: HttpHeaders::new(),
+			params: HashMap::new(),
+			state: Arc::new(())
+		});
+	}
+
+	/// Reads the body declared by the headers, refusing bodies larger than `max_body_size` before allocating anything
+	pub fn read_body(&mut self, buffer_reader: &mut impl BufRead, options: &HttpServerOptions, max_body_size: usize) -> Result<(), HttpRequestParseError> {
+		let transfer_encoding = self.headers.get_ignore_case("Transfer-Encoding");
+		let content_length = self.headers.get_ignore_case("Content-Length");
+
+		let body_content = match (transfer_encoding, content_length) {
+			// A request carrying both is ambiguous and a classic request smuggling vector (RFC 9112 section 6.3)
+			(Some(_), Some(_)) => return Err(HttpRequestParseError::MalformedHeader),
+
+			(Some(transfer_encoding), None) => {
+				// Chunked must be the final coding applied, otherwise we have no way to tell where the body ends
+				let codings = transfer_encoding.as_list();
+				let is_chunked = |coding: &String| coding.eq_ignore_ascii_case("chunked");
+
+				let (last_coding, other_codings) = match codings.split_last() {
+					Some(split) => split,
+					None => return Err(HttpRequestParseError::MalformedHeader)
+				};
+
+				// Chunked mustn't be applied more than once either (RFC 9112 section 7)
+				if !is_chunked(last_coding) || other_codings.iter().any(is_chunked) {
+					return Err(HttpRequestParseError::MalformedHeader);
+				}
+
+				// Only the chunked framing is understood - handing over a body still compressed (e.g. "gzip, chunked") would
+				// be indistinguishable from an uncompressed one (RFC 9112 section 6.1)
+				if !other_codings.is_empty() {
+					return Err(HttpRequestParseError::UnsupportedTransferCoding);
+				}
+
+				let (body_content, trailers) = Self::read_chunked_body(buffer_reader, options, max_body_size)?;
+
+				self.trailers = trailers;
+
+				body_content
+			},
+
+			(None, Some(content_length)) => {
+				// Several Content-Length values are only unambiguous if they all agree. Otherwise we could disagree with a
+				// proxy in front of us about where the body ends, letting part of it be read as another request.
+				let content_lengths = content_length
+					.as_list()
+					.iter()
+					.map(|content_length| match content_length.bytes().all(|byte| byte.is_ascii_digit()) {
+						true => content_length.parse::<usize>().ok(),
+						false => Option::None
+					})
+					.collect::<Option<Vec<usize>>>();
+
+				let content_length = match content_lengths.as_deref() {
+					Some([first, rest @ ..]) if rest.iter().all(|other| other == first) => *first,
+					_ => return Err(HttpRequestParseError::MalformedHeader)
+				};
 
-        if version.as_str() != "HTTP/1.1" {
-            return Err(HttpRequestParseError::UnsupportedVersion);
-        }
+				if content_length > max_body_size {
+					return Err(HttpRequestParseError::BodyTooLarge);
+				}
 
+				let mut body_content = vec![0; content_length];
+				buffer_reader.read_exact(&mut body_content)?;
+
+				body_content
+			},
+
+			(None, None) => return Ok(())
+		};
+
+		let content_type = self.headers.get_ignore_case("Content-Type").unwrap_or(&HttpHeaderValue::new("application/octet-stream".to_string())).clone();
+		let content_disposition = self.headers.get_ignore_case("Content-Disposition").cloned();
+
+		self.body = Some(
+			UploadedData::new(
+				content_type,
+				content_disposition,
+				body_content
+			)
+		);
+
+		return Ok(());
+	}
+
+	/// Reads header fields up to and including the empty line which terminates them
+	fn read_header_section(buffer_reader: &mut impl BufRead, options: &HttpServerOptions) -> Result<HttpHeaders, HttpRequestParseError> {
 		let mut header_string = String::new();
+		let mut header_count = 0;
 
 		loop {
-			let mut line = String::new();
-			buffer_reader.read_line(&mut line).unwrap();
+			let remaining_header_size = options.max_header_size.saturating_sub(header_string.len());
+
+			let line = match Self::read_line_limited(buffer_reader, remaining_header_size)? {
+				// The connection closing part way through the headers leaves us with an incomplete request
+				Some(line) if line.is_empty() => return Err(HttpRequestParseError::MalformedHeader),
+				Some(line) => line,
+				None => return Err(HttpRequestParseError::HeadersTooLarge)
+			};
 
 			if line == "\r\n" {
 				break;
 			}
 
+			// Whitespace before the colon (or a line folded onto the previous one) is rejected rather than trimmed, since
+			// recipients which disagree about the field's name can be made to disagree about how the message is framed
+			// (RFC 9112 section 5.1)
+			match line.split_once(':') {
+				Some((name, _value)) if utils::is_token(name) => (),
+				_ => return Err(HttpRequestParseError::MalformedHeader)
+			};
+
+			header_count += 1;
+
+			if header_count > options.max_header_count {
+				return Err(HttpRequestParseError::HeadersTooLarge);
+			}
+
 			header_string.push_str(&line);
 		}
-		
-		let headers = HttpHeaders::from_string(header_string);
 
-		let mut body = Option::None;
+		return Ok(HttpHeaders::from_string(header_string));
+	}
 
-        if headers.contains_key("Content-Length") {
-            let content_length = headers.get("Content-Length").unwrap().as_parameterized_header_value().value.parse::<usize>().unwrap();
-            let mut body_content = vec![0; content_length];
-            buffer_reader.read_exact(&mut body_content).unwrap();
+	/// Decodes a chunked body as described in RFC 9112 section 7.1, returning the body and any trailer fields
+	fn read_chunked_body(buffer_reader: &mut impl BufRead, options: &HttpServerOptions, max_body_size: usize) -> Result<(Vec<u8>, HttpHeaders), HttpRequestParseError> {
+		let mut body_content = Vec::new();
 
-			let content_type = headers.get("Content-Type").unwrap_or(&HttpHeaderValue::new("application/octet-stream".to_string())).clone();
-			let content_disposition = headers.get("Content-Disposition").and_then(|header| Some(header.clone()));
+		loop {
+			let chunk_size_line = match Self::read_line_limited(buffer_reader, MAX_CHUNK_SIZE_LINE_LENGTH)? {
+				Some(line) if line.ends_with("\r\n") => line,
+				_ => return Err(HttpRequestParseError::MalformedChunk)
+			};
 
-			body = Some(
-				UploadedData::new(
-					content_type,
-					content_disposition,
-					body_content
-				)
-			);
-        }
+			// Chunk extensions (";name=value" pairs after the size) carry no meaning we understand so they are discarded
+			let chunk_size = chunk_size_line.split(';').next().unwrap().trim_end_matches([' ', '\t', '\r', '\n']);
 
-		return Ok(Self {
-			method,
-			uri,
-			headers,
-			body
-		});
-    }
+			if chunk_size.is_empty() || !chunk_size.chars().all(|char| char.is_ascii_hexdigit()) {
+				return Err(HttpRequestParseError::MalformedChunk);
+			}
+
+			let chunk_size = match usize::from_str_radix(chunk_size, 16) {
+				Ok(chunk_size) => chunk_size,
+				Err(_e) => return Err(HttpRequestParseError::BodyTooLarge)
+			};
+
+			if chunk_size == 0 {
+				break;
+			}
+
+			if chunk_size > max_body_size - body_content.len() {
+				return Err(HttpRequestParseError::BodyTooLarge);
+			}
+
+			let chunk_start = body_content.len();
+
+			body_content.resize(chunk_start + chunk_size, 0);
+			buffer_reader.read_exact(&mut body_content[chunk_start..])?;
+
+			let mut chunk_terminator = [0; 2];
+			buffer_reader.read_exact(&mut chunk_terminator)?;
+
+			if &chunk_terminator != b"\r\n" {
+				return Err(HttpRequestParseError::MalformedChunk);
+			}
+		}
+
+		let trailers = Self::read_header_section(buffer_reader, options)?;
+
+		return Ok((body_content, trailers));
+	}
+
+	/// Reads a line of at most `limit` bytes (including the line ending). Returns `None` if the line is longer than that
+	/// and an empty string if the stream has ended.
+	fn read_line_limited(buffer_reader: &mut impl BufRead, limit: usize) -> Result<Option<String>, HttpRequestParseError> {
+		let mut line = String::new();
+
+		let read = buffer_reader.by_ref().take(limit as u64 + 1).read_line(&mut line)?;
+
+		if read > limit {
+			return Ok(Option::None);
+		}
+
+		return Ok(Some(line));
+	}
 
 	fn deserialize_request_line(request_line: String) -> Result<(HttpMethod, URI, String), HttpRequestParseError> {
-		let parts: Vec<String> = request_line.trim().split(" ").map(|part| String::from(part)).collect();
+		let parts: Vec<String> = request_line.trim().split(" ").map(String::from).collect();
 
 		if parts.len() != 3 {
 			return Err(HttpRequestParseError::MalformedRequestLine);
@@ -88,7 +310,7 @@ impl HttpRequest {
 
 		let uri = URI::from_string(parts.get(1).unwrap().to_string());
 
-		let method = match HttpMethod::from_string(parts.get(0).unwrap().to_string()) {
+		let method = match HttpMethod::from_string(parts.first().unwrap().to_string()) {
 			Ok(method) => method,
 			Err(_e) => return Err(HttpRequestParseError::UnrecognisedHttpMethod)
 		};
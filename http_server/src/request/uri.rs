@@ -8,6 +8,9 @@ pub struct URI {
     #[allow(dead_code)]
     pub query: HashMap<String, String>,
 
+    /// The query exactly as it was sent, without the leading "?", if there was one
+    pub query_string: Option<String>,
+
     #[allow(dead_code)]
     pub fragment: String,
 }
@@ -28,6 +31,7 @@ impl URI {
 
         let mut path = String::new();
         let mut query: HashMap<String, String> = HashMap::new();
+        let mut raw_query_string = Option::None;
         let mut fragment = String::new();
 
         while state != URIParsingState::Finished {
@@ -37,7 +41,7 @@ impl URI {
 
                     state = match scanner.finished() {
                         true => URIParsingState::Finished,
-                        false => match scanner.consume_exact(1).get(0).unwrap() {
+                        false => match scanner.consume_exact(1).first().unwrap() {
                             '?' => URIParsingState::Query,
                             '#' => URIParsingState::Fragment,
 
@@ -48,12 +52,14 @@ impl URI {
                 URIParsingState::Query => {
                     let query_string = scanner.consume_until_value('#').into_iter().collect::<String>();
 
+                    raw_query_string = Some(query_string.clone());
+
                     // NOTE: Query string format is not standardized - this implementation is in line with the
                     // algorithm recommended for decoding application/x-www-form-urlencoded payloads:
                     // https://www.w3.org/TR/2014/REC-html5-20141028/forms.html#url-encoded-form-data
 
                     // 1. Let strings be the result of strictly splitting the string payload on U+0026 AMPERSAND characters (&).
-                    let strings = query_string.split("&").map(|string| String::from(string)).collect::<Vec<String>>();
+                    let strings = query_string.split("&").map(String::from).collect::<Vec<String>>();
 
                     // 2. If the isindex flag is set and the first string in strings does not contain a "=" (U+003D)
                     // character, insert a "=" (U+003D) character at the start of the first string in strings.
@@ -114,6 +120,7 @@ impl URI {
         return Self {
             path,
             query,
+            query_string: raw_query_string,
             fragment
         };
     }
@@ -1,10 +1,62 @@
-use std::collections::HashMap;
+use std::{
+	collections::HashMap,
+	io::{
+		BufWriter,
+		Error as IoError,
+		ErrorKind,
+		Read,
+		Result as IoResult,
+		Write
+	},
+};
+
+/// Size of the chunks read from a streamed body
+const STREAM_CHUNK_SIZE: usize = 16 * 1024;
+
+type HttpResponseChunks = Box<dyn Iterator<Item = IoResult<Vec<u8>>> + Send>;
+type HttpResponseTrailers = Box<dyn FnOnce() -> HashMap<String, String> + Send>;
+
+enum HttpResponseBody {
+	Empty,
+	Bytes(Vec<u8>),
+	/// Written as it is produced, with the length announced up front
+	Sized(HttpResponseChunks, u64),
+	/// Written with the chunked transfer coding as the chunks are produced, so the length never needs to be known
+	Chunked(HttpResponseChunks)
+}
 
 pub struct HttpResponse {
 	status: u16,
 	status_text: String,
-	content: Option<String>,
-	headers: HashMap<String, String>
+	body: HttpResponseBody,
+	headers: HashMap<String, String>,
+	trailers: Option<HttpResponseTrailers>
+}
+
+/// Adapts a reader into an iterator of chunks so that it can be streamed as a chunked body
+struct ReaderChunks<R: Read> {
+	reader: R
+}
+
+impl<R: Read> Iterator for ReaderChunks<R> {
+	type Item = IoResult<Vec<u8>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut chunk = vec![0; STREAM_CHUNK_SIZE];
+
+		loop {
+			return match self.reader.read(&mut chunk) {
+				Ok(0) => Option::None,
+				Ok(read) => {
+					chunk.truncate(read);
+
+					Some(Ok(chunk))
+				},
+				Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+				Err(error) => Some(Err(error))
+			};
+		}
+	}
 }
 
 impl HttpResponse {
@@ -12,8 +64,9 @@ impl HttpResponse {
 		return HttpResponse {
 			status: 200,
 			status_text: String::from("OK"),
-			content: Option::None,
-			headers: HashMap::new()
+			body: HttpResponseBody::Empty,
+			headers: HashMap::new(),
+			trailers: Option::None
 		};
 	}
 
@@ -61,6 +114,7 @@ impl HttpResponse {
 			421 => String::from("Misdirected Request"),
 			422 => String::from("Unprocessable Content"),
 			426 => String::from("Upgrade Required"),
+			431 => String::from("Request Header Fields Too Large"),
 
 			500 => String::from("Internal Server Error"),
 			501 => String::from("Not Implemented"),
@@ -75,8 +129,53 @@ impl HttpResponse {
 		return self;
 	}
 
-	pub fn content(mut self, content: String) -> Self {
-		self.content = Option::Some(content);
+	/// Sets the body to the given bytes, e.g. a `String`, `&str` or `Vec<u8>`
+	pub fn content(mut self, content: impl Into<Vec<u8>>) -> Self {
+		self.body = HttpResponseBody::Bytes(content.into());
+
+		return self;
+	}
+
+	/// Sets a plain text body along with a matching Content-Type
+	pub fn text(self, content: impl Into<String>) -> Self {
+		return self
+			.header(String::from("Content-Type"), String::from("text/plain; charset=utf-8"))
+			.content(content.into());
+	}
+
+	/// Sets an HTML body along with a matching Content-Type
+	pub fn html(self, content: impl Into<String>) -> Self {
+		return self
+			.header(String::from("Content-Type"), String::from("text/html; charset=utf-8"))
+			.content(content.into());
+	}
+
+	/// Streams the body from a reader as it is written to the client, without buffering it in memory
+	pub fn stream(mut self, reader: impl Read + Send + 'static) -> Self {
+		self.body = HttpResponseBody::Chunked(Box::new(ReaderChunks { reader }));
+
+		return self;
+	}
+
+	/// Streams exactly `length` bytes from a reader, sending a Content-Length rather than using the chunked transfer
+	/// coding. If the reader ends early the connection is closed since the response can't be completed.
+	pub fn stream_with_length(mut self, reader: impl Read + Send + 'static, length: u64) -> Self {
+		self.body = HttpResponseBody::Sized(Box::new(ReaderChunks { reader: reader.take(length) }), length);
+
+		return self;
+	}
+
+	/// Sends each chunk to the client as soon as the iterator produces it
+	pub fn chunks(mut self, chunks: impl IntoIterator<Item = Vec<u8>, IntoIter: Send + 'static>) -> Self {
+		self.body = HttpResponseBody::Chunked(Box::new(chunks.into_iter().map(Ok)));
+
+		return self;
+	}
+
+	/// Trailer fields to send after a streamed body. They are only computed once the whole body has been written, so
+	/// they can describe it (e.g. a checksum). Ignored unless the body is streamed.
+	pub fn trailers(mut self, trailers: impl FnOnce() -> HashMap<String, String> + Send + 'static) -> Self {
+		self.trailers = Some(Box::new(trailers));
 
 		return self;
 	}
@@ -87,21 +186,98 @@ impl HttpResponse {
 		return self;
 	}
 
-	pub fn serialize(self) -> String {
-		let status_line = format!("HTTP/1.1 {} {}", self.status, self.status_text);
+	pub(crate) fn get_header(&self, key: &str) -> Option<&String> {
+		return self.headers
+			.iter()
+			.find(|(header_key, _value)| header_key.eq_ignore_ascii_case(key))
+			.map(|(_key, value)| value);
+	}
+
+	pub fn serialize(self) -> Vec<u8> {
+		let mut serialized = Vec::new();
 
-		let content = self.content.unwrap_or(String::from(""));
+		// Writing into memory can only fail if a streamed body fails to produce its content
+		let _ = self.write_to(&mut serialized);
+
+		return serialized;
+	}
+
+	/// Writes the response to the client, streaming the body if it is chunked
+	pub fn write_to(self, writer: &mut impl Write) -> IoResult<()> {
+		return self.write(writer, true);
+	}
+
+	/// Writes the response, leaving out the body (but not the headers describing it) if `include_body` is false, as for
+	/// a response to a HEAD request
+	pub(crate) fn write(self, writer: &mut impl Write, include_body: bool) -> IoResult<()> {
+		let mut writer = BufWriter::new(writer);
+
+		let status_line = format!("HTTP/1.1 {} {}", self.status, self.status_text);
 
 		let mut headers = self.headers;
 
-		headers.insert(String::from("Content-Length"), content.len().to_string());
+		// These responses never have content (RFC 9110 sections 6.4.1 and 15.4.5), so a length of 0 would be misleading
+		let has_content = !(self.status < 200 || self.status == 204 || self.status == 304);
 
-		let headers_string = headers
-			.iter()
-			.map(|(key, value)| format!("{key}: {value}"))
-			.collect::<Vec<String>>()
-			.join("\r\n");
+		match &self.body {
+			_ if !has_content => None,
+			HttpResponseBody::Empty => headers.insert(String::from("Content-Length"), String::from("0")),
+			HttpResponseBody::Bytes(content) => headers.insert(String::from("Content-Length"), content.len().to_string()),
+			HttpResponseBody::Sized(_, length) => headers.insert(String::from("Content-Length"), length.to_string()),
+			HttpResponseBody::Chunked(_) => headers.insert(String::from("Transfer-Encoding"), String::from("chunked"))
+		};
 
-		return format!("{status_line}\r\n{headers_string}\r\n\r\n{content}");
+		write!(writer, "{status_line}\r\n{}\r\n", Self::serialize_fields(&headers))?;
+
+		match self.body {
+			_ if !has_content || !include_body => (),
+			HttpResponseBody::Empty => (),
+			HttpResponseBody::Bytes(content) => writer.write_all(&content)?,
+			HttpResponseBody::Sized(chunks, length) => {
+				let mut written = 0;
+
+				for chunk in chunks {
+					let chunk = chunk?;
+
+					writer.write_all(&chunk)?;
+					written += chunk.len() as u64;
+				}
+
+				if written != length {
+					return Err(IoError::from(ErrorKind::UnexpectedEof));
+				}
+			},
+			HttpResponseBody::Chunked(chunks) => {
+				for chunk in chunks {
+					let chunk = chunk?;
+
+					// A zero length chunk would mark the end of the body
+					if chunk.is_empty() {
+						continue;
+					}
+
+					write!(writer, "{:x}\r\n", chunk.len())?;
+					writer.write_all(&chunk)?;
+					writer.write_all(b"\r\n")?;
+
+					// Each chunk is sent as soon as it is available rather than waiting for the buffer to fill
+					writer.flush()?;
+				}
+
+				let trailers = self.trailers.map(|trailers| trailers()).unwrap_or_default();
+
+				write!(writer, "0\r\n{}\r\n", Self::serialize_fields(&trailers))?;
+			}
+		};
+
+		return writer.flush();
+	}
+
+	/// Serializes header or trailer fields, each terminated by a CRLF
+	fn serialize_fields(fields: &HashMap<String, String>) -> String {
+		return fields
+			.iter()
+			.map(|(key, value)| format!("{key}: {value}\r\n"))
+			.collect::<String>();
 	}
 }
@@ -4,62 +4,35 @@ use std::fmt::{
 	Result as FormatResult
 };
 
+use crate::path_pattern::PathPattern;
+
 use super::{
 	HttpRequest,
 	HttpResponse,
 	HttpMethod
 };
 
-pub type HttpRouteCallback = fn(HttpRequest) -> HttpResponse;
+/// Handles requests matching a route. Closures may capture whatever they need, and the server's application state is
+/// available through `HttpRequest::state`.
+pub type HttpRouteCallback<S = ()> = Box<dyn Fn(HttpRequest<S>) -> HttpResponse + Send + Sync>;
 
-pub struct HttpRoute {
+pub struct HttpRoute<S = ()> {
 	pub method: HttpMethod,
+	/// Pattern the request path must match. Segments may be literal, contain `*` wildcards, capture a parameter
+	/// (`:name`) or, as the last segment, capture the rest of the path (`*name`). Captured values end up in
+	/// `HttpRequest::params`.
+	///
+	/// A `*` inside a segment never matches across a `/`, so `/documents/*.txt` matches `/documents/a.txt` but not
+	/// `/documents/a/b.txt`. Only a trailing `*` or `*name` matches the rest of the path, e.g. `/documents/*path`.
 	pub path_pattern: String,
-	pub callback: HttpRouteCallback,
-}
-
-impl HttpRoute {
-	pub fn matches(&self, request: &HttpRequest) -> bool {
-		return self.method == request.method && self.path_matches(&request.uri.path, &self.path_pattern, request.uri.path.len(), self.path_pattern.len());
-	}
-
-	fn path_matches(&self, path: &String, pattern: &String, path_index: usize, pattern_index: usize) -> bool {
-		// If the pattern is empty it can only match an empty string
-		if pattern_index == 0 {
-			return path_index == 0;
-		}
-
-		// If the path is empty the only matching pattern is a string (potentially zero length) entirely of "*"
-		if path_index == 0 {
-			for char in pattern.chars() {
-				if char != '*' {
-					return false;
-				}
-			}
+	pub(crate) pattern: PathPattern,
+	pub callback: HttpRouteCallback<S>,
 
-			return true;
-		}
-
-		let path_char = path.chars().collect::<Vec<char>>().get(path_index - 1).unwrap().clone();
-		let pattern_char = pattern.chars().collect::<Vec<char>>().get(pattern_index - 1).unwrap().clone();
-
-		// If the current character of the pattern matches the current character of the path - move onto the next character
-		if path_char == pattern_char {
-			return self.path_matches(path, pattern, path_index - 1, pattern_index - 1)
-		}
-
-		// If the current character of the pattern is a '*' there are two cases which could return true:
-		//  1. We have completed matching characters for this selector and thus remove it from the pattern and continue
-		//  2. We haven't completed matching characters for this selector and thus remove the next character from the path and continue
-		if pattern_char == '*' {
-			return self.path_matches(path, pattern, path_index, pattern_index - 1) || self.path_matches(path, pattern, path_index - 1, pattern_index);
-		}
-
-		return false;
-	}
+	/// Overrides the server's `max_body_size` for requests handled by this route
+	pub max_body_size: Option<usize>,
 }
 
-impl Display for HttpRoute {
+impl<S> Display for HttpRoute<S> {
 	fn fmt(&self, f: &mut Formatter) -> FormatResult {
 		return write!(f, "{} {}", self.method, self.path_pattern);
 	}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use crate::{
+	path_pattern::{
+		PathPattern,
+		PatternSegment
+	},
+	HttpMethod,
+	HttpRoute,
+};
+
+/// A node of the route tree, standing for one segment of a path pattern
+#[derive(Default)]
+struct RouteNode {
+	/// Routes whose pattern ends at this node, by method, as indices into `Router::routes`
+	endpoints: HashMap<HttpMethod, usize>,
+	literal_children: HashMap<String, RouteNode>,
+	/// Parameters and bare `*` segments, which match the same segments and so share a node
+	param_child: Option<Box<RouteNode>>,
+	/// Segments containing `*` wildcards, tried in the order they were registered
+	glob_children: Vec<(String, RouteNode)>,
+	/// Routes ending with a catch-all after this node, by method
+	catch_all_endpoints: HashMap<HttpMethod, usize>
+}
+
+/// Routes compiled into a tree of path segments so that finding the route for a request only walks the segments of its
+/// path instead of trying every route in turn. Where several routes match, the most specific wins: at each segment a
+/// literal beats a parameter, which beats a segment containing wildcards, which beats a catch-all.
+pub struct Router<S> {
+	routes: Vec<HttpRoute<S>>,
+	root: RouteNode
+}
+
+impl<S> Router<S> {
+	pub fn new() -> Self {
+		return Self {
+			routes: Vec::new(),
+			root: RouteNode::default()
+		};
+	}
+
+	pub fn routes(&self) -> &[HttpRoute<S>] {
+		return &self.routes;
+	}
+
+	/// Adds a route to the tree. Panics if a route for the same method already matches exactly the same paths (e.g.
+	/// `/users/:id` and `/users/:name`, or `/a/*/b` and `/a/:x/b`), since only one of them could ever be used.
+	pub fn insert(&mut self, route: HttpRoute<S>) -> &mut HttpRoute<S> {
+		let endpoints = Self::endpoints_for(&mut self.root, route.pattern.segments());
+
+		if let Some(existing) = endpoints.get(&route.method) {
+			panic!("Conflicting routes: {} and {} match the same requests", self.routes[*existing], route);
+		}
+
+		endpoints.insert(route.method.clone(), self.routes.len());
+
+		self.routes.push(route);
+
+		return self.routes.last_mut().unwrap();
+	}
+
+	/// Finds the most specific route for the method and path, along with the values captured from the path
+	pub fn find(&self, method: &HttpMethod, path: &str) -> Option<(&HttpRoute<S>, HashMap<String, String>)> {
+		let segments = PathPattern::split(path).collect::<Vec<&str>>();
+		let mut captures = Vec::new();
+
+		let index = Self::search(&self.root, &segments, &mut captures, &mut |endpoints| endpoints.get(method).copied())?;
+		let route = &self.routes[index];
+
+		let params = route.pattern
+			.capture_names()
+			.into_iter()
+			.zip(captures)
+			.filter_map(|(name, value)| name.map(|name| (name, PathPattern::decode(&value))))
+			.collect();
+
+		return Some((route, params));
+	}
+
+	/// The methods of every route matching the path, in the order the routes were registered
+	pub fn allowed_methods(&self, path: &str) -> Vec<HttpMethod> {
+		let segments = PathPattern::split(path).collect::<Vec<&str>>();
+		let mut matched = Vec::new();
+
+		// Never selecting an endpoint makes the search visit every route which matches
+		Self::search(&self.root, &segments, &mut Vec::new(), &mut |endpoints| {
+			matched.extend(endpoints.values().copied());
+
+			return Option::None;
+		});
+
+		matched.sort();
+
+		return matched.into_iter().map(|index| self.routes[index].method.clone()).collect();
+	}
+
+	fn endpoints_for<'a>(mut node: &'a mut RouteNode, segments: &[PatternSegment]) -> &'a mut HashMap<HttpMethod, usize> {
+		for segment in segments {
+			node = match segment {
+				PatternSegment::Literal(literal) => node.literal_children.entry(literal.clone()).or_default(),
+				PatternSegment::Glob(glob) => match node.glob_children.iter().position(|(existing, _child)| existing == glob) {
+					Some(position) => &mut node.glob_children[position].1,
+					None => {
+						node.glob_children.push((glob.clone(), RouteNode::default()));
+
+						&mut node.glob_children.last_mut().unwrap().1
+					}
+				},
+				PatternSegment::Param(_name) => node.param_child.get_or_insert_with(Box::default),
+				PatternSegment::CatchAll(_name) => return &mut node.catch_all_endpoints
+			};
+		}
+
+		return &mut node.endpoints;
+	}
+
+	/// Walks the tree depth first in order of specificity, backtracking when a more specific branch has no route that
+	/// `select` accepts, and returns the first route it does accept. `captures` holds the values captured along the way.
+	fn search(
+		node: &RouteNode,
+		segments: &[&str],
+		captures: &mut Vec<String>,
+		select: &mut impl FnMut(&HashMap<HttpMethod, usize>) -> Option<usize>
+	) -> Option<usize> {
+		match segments.split_first() {
+			Some((segment, remaining)) => {
+				if let Some(child) = node.literal_children.get(*segment) {
+					if let Some(index) = Self::search(child, remaining, captures, select) {
+						return Some(index);
+					}
+				}
+
+				if let Some(child) = node.param_child.as_ref().filter(|_child| !segment.is_empty()) {
+					captures.push(String::from(*segment));
+
+					if let Some(index) = Self::search(child, remaining, captures, select) {
+						return Some(index);
+					}
+
+					captures.pop();
+				}
+
+				for (glob, child) in &node.glob_children {
+					if PathPattern::glob_matches(glob, segment) {
+						if let Some(index) = Self::search(child, remaining, captures, select) {
+							return Some(index);
+						}
+					}
+				}
+			},
+			None => {
+				if let Some(index) = select(&node.endpoints) {
+					return Some(index);
+				}
+			}
+		};
+
+		// A catch-all matches whatever is left of the path, even nothing
+		let index = select(&node.catch_all_endpoints)?;
+
+		captures.push(segments.join("/"));
+
+		return Some(index);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		path_pattern::PathPattern,
+		HttpMethod,
+		HttpResponse,
+		HttpRoute,
+	};
+
+	use super::Router;
+
+	fn router(routes: &[(HttpMethod, &str)]) -> Router<()> {
+		let mut router = Router::new();
+
+		for (method, path_pattern) in routes {
+			router.insert(HttpRoute {
+				method: method.clone(),
+				path_pattern: String::from(*path_pattern),
+				pattern: PathPattern::parse(path_pattern),
+				callback: Box::new(|_request| HttpResponse::new()),
+				max_body_size: Option::None
+			});
+		}
+
+		return router;
+	}
+
+	fn get_routes(path_patterns: &[&str]) -> Router<()> {
+		return router(&path_patterns.iter().map(|path_pattern| (HttpMethod::GET, *path_pattern)).collect::<Vec<_>>());
+	}
+
+	/// The pattern of the route found for the request along with its params, sorted so they can be compared
+	fn find(router: &Router<()>, method: HttpMethod, path: &str) -> Option<(String, Vec<(String, String)>)> {
+		let (route, params) = router.find(&method, path)?;
+		let mut params = params.into_iter().collect::<Vec<(String, String)>>();
+
+		params.sort();
+
+		return Some((route.path_pattern.clone(), params));
+	}
+
+	fn found(path_pattern: &str, params: &[(&str, &str)]) -> Option<(String, Vec<(String, String)>)> {
+		let params = params.iter().map(|(name, value)| (String::from(*name), String::from(*value))).collect();
+
+		return Some((String::from(path_pattern), params));
+	}
+
+	#[test]
+	fn literal_beats_param_beats_glob_beats_catch_all() {
+		let router = get_routes(&["/a/*rest", "/a/*.txt", "/a/:name", "/a/b"]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/a/b"), found("/a/b", &[]));
+		assert_eq!(find(&router, HttpMethod::GET, "/a/c.txt"), found("/a/:name", &[("name", "c.txt")]));
+		assert_eq!(find(&router, HttpMethod::GET, "/a/c/d"), found("/a/*rest", &[("rest", "c/d")]));
+
+		let router = get_routes(&["/g/*rest", "/g/*.txt"]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/g/c.txt"), found("/g/*.txt", &[]));
+		assert_eq!(find(&router, HttpMethod::GET, "/g/c.png"), found("/g/*rest", &[("rest", "c.png")]));
+	}
+
+	#[test]
+	fn params_and_globs_match_single_segments() {
+		let router = get_routes(&["/documents/*.txt", "/users/:id"]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/documents/a.txt"), found("/documents/*.txt", &[]));
+		assert_eq!(find(&router, HttpMethod::GET, "/documents/a/b.txt"), Option::None);
+		assert_eq!(find(&router, HttpMethod::GET, "/users/"), Option::None);
+		assert_eq!(find(&router, HttpMethod::GET, "/users/1/2"), Option::None);
+	}
+
+	#[test]
+	fn backtracks_out_of_more_specific_branches() {
+		let router = get_routes(&["/users/me/settings", "/users/:id/posts", "/users/*.json/raw"]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/users/me/settings"), found("/users/me/settings", &[]));
+		assert_eq!(find(&router, HttpMethod::GET, "/users/me/posts"), found("/users/:id/posts", &[("id", "me")]));
+		assert_eq!(find(&router, HttpMethod::GET, "/users/me.json/raw"), found("/users/*.json/raw", &[]));
+	}
+
+	#[test]
+	fn backtracks_when_only_a_less_specific_route_has_the_method() {
+		let router = router(&[(HttpMethod::GET, "/users/me"), (HttpMethod::POST, "/users/:id")]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/users/me"), found("/users/me", &[]));
+		assert_eq!(find(&router, HttpMethod::POST, "/users/me"), found("/users/:id", &[("id", "me")]));
+		assert_eq!(router.allowed_methods("/users/me"), vec![HttpMethod::GET, HttpMethod::POST]);
+		assert_eq!(router.allowed_methods("/users/you"), vec![HttpMethod::POST]);
+	}
+
+	#[test]
+	fn abandoned_branches_leave_no_captures_behind() {
+		let router = get_routes(&["/p/:a/:b/x", "/p/:first/*rest"]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/p/1/2/x"), found("/p/:a/:b/x", &[("a", "1"), ("b", "2")]));
+		assert_eq!(find(&router, HttpMethod::GET, "/p/1/2/y"), found("/p/:first/*rest", &[("first", "1"), ("rest", "2/y")]));
+
+		let router = get_routes(&["/q/:a/x", "/q/*.txt/y", "/q/*/z/:c"]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/q/f.txt/y"), found("/q/*.txt/y", &[]));
+		assert_eq!(find(&router, HttpMethod::GET, "/q/f.txt/z/3"), found("/q/*/z/:c", &[("c", "3")]));
+	}
+
+	#[test]
+	fn captures_are_percent_decoded() {
+		let router = get_routes(&["/files/:name"]);
+
+		assert_eq!(find(&router, HttpMethod::GET, "/files/a%20b"), found("/files/:name", &[("name", "a b")]));
+	}
+
+	#[test]
+	fn routes_for_different_methods_do_not_conflict() {
+		router(&[(HttpMethod::GET, "/users/:id"), (HttpMethod::DELETE, "/users/:name")]);
+	}
+
+	#[test]
+	#[should_panic(expected = "Conflicting routes")]
+	fn params_with_different_names_conflict() {
+		get_routes(&["/users/:id", "/users/:name"]);
+	}
+
+	#[test]
+	#[should_panic(expected = "Conflicting routes")]
+	fn bare_wildcard_segments_conflict_with_params() {
+		get_routes(&["/a/*/b", "/a/:x/b"]);
+	}
+
+	#[test]
+	#[should_panic(expected = "Conflicting routes")]
+	fn catch_alls_with_different_names_conflict() {
+		get_routes(&["/files/*rest", "/files/*"]);
+	}
+}
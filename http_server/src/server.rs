@@ -1,101 +1,512 @@
 use std::{
-	fs,
-	io::Write,
+	collections::HashMap,
+	io::{
+		BufRead,
+		BufReader,
+		Result as IoResult
+	},
 	net::{
+		SocketAddr,
 		TcpListener,
-		TcpStream
+		TcpStream,
+		ToSocketAddrs
+	},
+	panic::{
+		self,
+		AssertUnwindSafe
+	},
+	path::PathBuf,
+	sync::{
+		mpsc::{
+			self,
+			TrySendError
+		},
+		Arc,
+		Mutex
+	},
+	thread,
+	time::{
+		Duration,
+		Instant
 	},
-	path::Path,
 };
 
-use crate::mime_type::MimeType;
+use crate::{
+	mime_type::MimeTypeOverrides,
+	path_pattern::PathPattern,
+	router::Router,
+	request::HttpRequestParseError,
+	shutdown::TrackedConnectionGuard,
+	static_files::{
+		EmbeddedDirectory,
+		StaticDirectory,
+		StaticMount,
+		StaticOptions
+	},
+	utils::DeadlineStream,
+};
 
 use super::{
 	HttpMethod,
 	HttpRequest,
 	HttpResponse,
 	HttpRoute,
-	HttpRouteCallback,
+	HttpServerOptions,
+	EmbeddedFile,
+	ShutdownHandle,
 };
 
-pub struct HttpServer {
-	routes: Vec<HttpRoute>,
-	static_directories: Vec<String>
+/// A server bound to a socket which has not started accepting connections yet
+pub struct BoundHttpServer<'a, S = ()> {
+	server: &'a HttpServer<S>,
+	tcp_listener: TcpListener,
+	local_addr: SocketAddr
+}
+
+impl<S: Send + Sync + 'static> BoundHttpServer<'_, S> {
+	/// The address actually bound, including the port picked by the OS when binding to port 0
+	pub fn local_addr(&self) -> SocketAddr {
+		return self.local_addr;
+	}
+
+	/// Accepts connections until the server is shut down
+	pub fn listen(self) -> IoResult<()> {
+		self.server.accept_connections(self.tcp_listener, self.local_addr);
+
+		return Ok(());
+	}
+}
+
+/// `S` is the type of the application state shared with every route callback through `HttpRequest::state`
+pub struct HttpServer<S = ()> {
+	router: Router<S>,
+	static_mounts: Vec<Box<dyn StaticMount>>,
+	mime_types: MimeTypeOverrides,
+	options: HttpServerOptions,
+	shutdown: ShutdownHandle,
+	state: Arc<S>
 }
 
 impl HttpServer {
 	pub fn new() -> Self {
+		return Self::with_options(HttpServerOptions::default());
+	}
+
+	pub fn with_options(options: HttpServerOptions) -> Self {
+		return Self::with_state_and_options((), options);
+	}
+}
+
+impl<S: Send + Sync + 'static> HttpServer<S> {
+	/// Creates a server whose route callbacks can all borrow `state` (e.g. a connection pool, configuration or
+	/// counters) through `HttpRequest::state`. Anything mutable needs its own synchronisation since callbacks run
+	/// concurrently.
+	pub fn with_state(state: S) -> Self {
+		return Self::with_state_and_options(state, HttpServerOptions::default());
+	}
+
+	pub fn with_state_and_options(state: S, options: HttpServerOptions) -> Self {
 		return Self {
-			routes: Vec::new(),
-			static_directories: Vec::new()
+			router: Router::new(),
+			static_mounts: Vec::new(),
+			mime_types: MimeTypeOverrides::default(),
+			options,
+			shutdown: ShutdownHandle::new(),
+			state: Arc::new(state)
 		};
 	}
 
-	pub fn get(&mut self, path_pattern: String, callback: HttpRouteCallback) -> () {
-		self.routes.push(HttpRoute {
-			method: HttpMethod::GET,
-			path_pattern,
-			callback
-		});
+	/// Returns a handle which can be used from another thread to stop `listen`
+	pub fn shutdown_handle(&self) -> ShutdownHandle {
+		return self.shutdown.clone();
 	}
 
-	pub fn post(&mut self, path_pattern: String, callback: HttpRouteCallback) -> () {
-		self.routes.push(HttpRoute {
-			method: HttpMethod::POST,
+	/// Registers a callback for requests with the given method whose path matches the pattern. Where several routes
+	/// match a request the most specific one handles it, whatever order they were registered in. Panics if another
+	/// route for the method already matches exactly the same paths. Use this for extension methods such as
+	/// `HttpMethod::Extension(String::from("PURGE"))`.
+	pub fn route(
+		&mut self,
+		method: HttpMethod,
+		path_pattern: String,
+		callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static
+	) -> &mut HttpRoute<S> {
+		return self.router.insert(HttpRoute {
+			method,
+			pattern: PathPattern::parse(&path_pattern),
 			path_pattern,
-			callback
+			callback: Box::new(callback),
+			max_body_size: Option::None
 		});
 	}
 
+	pub fn get(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::GET, path_pattern, callback);
+	}
+
+	pub fn head(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::HEAD, path_pattern, callback);
+	}
+
+	pub fn post(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::POST, path_pattern, callback);
+	}
+
+	pub fn put(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::PUT, path_pattern, callback);
+	}
+
+	pub fn delete(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::DELETE, path_pattern, callback);
+	}
+
+	pub fn connect(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::CONNECT, path_pattern, callback);
+	}
+
+	pub fn options(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::OPTIONS, path_pattern, callback);
+	}
+
+	pub fn trace(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::TRACE, path_pattern, callback);
+	}
+
+	pub fn patch(&mut self, path_pattern: String, callback: impl Fn(HttpRequest<S>) -> HttpResponse + Send + Sync + 'static) -> &mut HttpRoute<S> {
+		return self.route(HttpMethod::PATCH, path_pattern, callback);
+	}
+
 	pub fn serve_static(&mut self, directory_path: String) -> () {
-		self.static_directories.push(directory_path);
+		self.serve_static_with_options(directory_path, StaticOptions::default());
+	}
+
+	pub fn serve_static_with_options(&mut self, directory_path: String, options: StaticOptions) -> () {
+		self.serve_static_at(String::from("/"), directory_path, options);
+	}
+
+	/// Serves the files in a directory below a URL prefix, e.g. `/assets/app.js` from `<directory_path>/app.js`. Mounts
+	/// with longer prefixes are tried first, and mounts with the same prefix in the order they were added.
+	pub fn serve_static_at(&mut self, url_prefix: String, directory_path: String, options: StaticOptions) -> () {
+		self.mount_static(Box::new(StaticDirectory::new(&url_prefix, PathBuf::from(directory_path), options)));
+	}
+
+	/// Serves files embedded into the binary with `embed_directory` and `include_embedded!`, like `serve_static`
+	pub fn serve_embedded(&mut self, files: &'static [EmbeddedFile]) -> () {
+		self.serve_embedded_at(String::from("/"), files, StaticOptions::default());
+	}
+
+	/// Serves files embedded into the binary below a URL prefix, like `serve_static_at`
+	pub fn serve_embedded_at(&mut self, url_prefix: String, files: &'static [EmbeddedFile], options: StaticOptions) -> () {
+		self.mount_static(Box::new(EmbeddedDirectory::new(&url_prefix, files, options)));
+	}
+
+	fn mount_static(&mut self, static_mount: Box<dyn StaticMount>) -> () {
+		let position = self.static_mounts
+			.iter()
+			.position(|existing| existing.prefix_length() < static_mount.prefix_length())
+			.unwrap_or(self.static_mounts.len());
+
+		self.static_mounts.insert(position, static_mount);
+	}
+
+	/// Sets the Content-Type used for static files with the given extension, taking precedence over the built-in table
+	pub fn register_mime_type(&mut self, extension: String, mime_type: String) -> () {
+		self.mime_types.insert(extension, mime_type);
 	}
 
-	pub fn listen(&self, port: u16) -> () {
-		let tcp_listener = TcpListener::bind(format!("0.0.0.0:{}", port)).unwrap();
+	/// Binds the server to the given address without accepting connections yet, so that the caller can find out which
+	/// address was bound (e.g. when binding to port 0) before calling `listen`
+	pub fn bind(&self, address: impl ToSocketAddrs) -> IoResult<BoundHttpServer<'_, S>> {
+		let tcp_listener = TcpListener::bind(address)?;
+		let local_addr = tcp_listener.local_addr()?;
 
-		for stream in tcp_listener.incoming().map(|stream| stream.unwrap()) {
-			self.handle_connection(stream);
+		return Ok(BoundHttpServer {
+			server: self,
+			tcp_listener,
+			local_addr
+		});
+	}
+
+	/// Binds to the given port on all IPv4 interfaces and accepts connections until the server is shut down
+	pub fn listen(&self, port: u16) -> IoResult<()> {
+		return self.bind(("0.0.0.0", port))?.listen();
+	}
+
+	fn accept_connections(&self, tcp_listener: TcpListener, local_addr: SocketAddr) -> () {
+		// If shutdown was requested before the server even started there is nothing to do
+		if self.shutdown.set_listener_address(local_addr) {
+			return;
 		}
+
+		let (sender, receiver) = mpsc::sync_channel::<TcpStream>(self.options.queue_depth);
+		let receiver = Mutex::new(receiver);
+
+		thread::scope(|scope| {
+			let workers = (0..self.options.worker_threads.max(1)).map(|_| {
+				scope.spawn(|| {
+					loop {
+						// The lock is only held while waiting for a connection so that other workers can pick up work
+						let next_stream = receiver.lock().unwrap().recv();
+
+						let stream = match next_stream {
+							Ok(stream) => stream,
+							Err(_e) => break
+						};
+
+						// Connections still queued once the shutdown deadline has passed are dropped without a response
+						let connection = match self.shutdown.track(&stream) {
+							Some(connection) => connection,
+							None => continue
+						};
+
+						// Panics from route callbacks are answered with a 500 by `handle_connection`. Anything else which panics
+						// (e.g. a streamed body's iterator) should only cost the connection it was handling, not the worker
+						let _ = panic::catch_unwind(AssertUnwindSafe(|| self.handle_connection(stream, &connection)));
+					}
+				})
+			}).collect::<Vec<_>>();
+
+			for stream in tcp_listener.incoming() {
+				if self.shutdown.is_shutdown_requested() {
+					break;
+				}
+
+				let stream = match stream {
+					Ok(stream) => stream,
+					Err(_e) => continue
+				};
+
+				// If every worker is busy and the queue is full, shed load rather than letting the backlog grow unbounded
+				if let Err(TrySendError::Full(mut stream)) = sender.try_send(stream) {
+					// Rejecting the connection mustn't leave the accept loop stuck on a client that isn't reading
+					if stream.set_write_timeout(Some(self.options.write_timeout)).is_ok() {
+						Self::reject(&mut stream, 503);
+					}
+				}
+			}
+
+			// Workers finish the connections already queued and then exit once the queue is closed
+			drop(sender);
+
+			let deadline = self.shutdown.deadline().unwrap_or_else(Instant::now);
+
+			while Instant::now() < deadline && !workers.iter().all(|worker| worker.is_finished()) {
+				thread::sleep(Duration::from_millis(10));
+			}
+
+			// Anything still running has overstayed the grace period. Closing the sockets unblocks workers stuck on IO,
+			// though a route callback that never returns will still hold up `listen`
+			self.shutdown.close_connections();
+		});
 	}
 
-	fn handle_connection(&self, mut stream: TcpStream) -> () {
-		let response = match HttpRequest::from_stream(&mut stream) {
-			Ok(request) => self.handle_request(request),
-			Err(_e) => HttpResponse::new().status(400)
+	fn handle_connection(&self, mut stream: TcpStream, connection: &TrackedConnectionGuard) -> () {
+		let mut buffer_reader = match stream.try_clone() {
+			Ok(reader_stream) => BufReader::new(DeadlineStream::new(reader_stream)),
+			Err(_e) => return
 		};
 
-		stream.write_all(response.serialize().as_bytes()).unwrap();
-	}
+		if stream.set_write_timeout(Some(self.options.write_timeout)).is_err() {
+			return;
+		}
 
-	fn handle_request(&self, request: HttpRequest) -> HttpResponse {
-		// Find the matching route and return the result of the callback
-		for route in &self.routes {
-			if route.matches(&request) {
-				return (route.callback)(request);
+		let max_requests = self.options.max_requests_per_connection.max(1);
+		let mut handled_requests = 0;
+
+		loop {
+			// A new connection has the header read timeout to send its whole first request. Once a request has been served
+			// the connection is only kept open for as long as the keep-alive timeout allows
+			let idle_deadline = Instant::now() + match handled_requests {
+				0 => self.options.header_read_timeout,
+				_ => self.options.keep_alive_timeout
+			};
+
+			if !connection.set_idle(handled_requests > 0) {
+				return;
+			}
+
+			buffer_reader.get_mut().set_deadline(Some(idle_deadline));
+
+			let request_started = matches!(buffer_reader.fill_buf(), Ok(buffer) if !buffer.is_empty());
+
+			connection.set_idle(false);
+
+			// The client hung up or went quiet between requests - there is nobody to send a response to
+			if !request_started {
+				return;
+			}
+
+			let header_deadline = match handled_requests {
+				0 => idle_deadline,
+				_ => Instant::now() + self.options.header_read_timeout
+			};
+
+			buffer_reader.get_mut().set_deadline(Some(header_deadline));
+
+			let request = HttpRequest::from_stream(&mut buffer_reader, &self.options).and_then(|mut request| {
+				let max_body_size = self.find_route(&request)
+					.and_then(|(route, _params)| route.max_body_size)
+					.unwrap_or(self.options.max_body_size);
+
+				buffer_reader.get_mut().set_deadline(Some(Instant::now() + self.options.body_read_timeout));
+
+				return request.read_body(&mut buffer_reader, &self.options, max_body_size).map(|_| request);
+			});
+
+			let request = match request {
+				Ok(request) => request,
+
+				Err(HttpRequestParseError::TimedOut) => return Self::reject(&mut stream, 408),
+				Err(HttpRequestParseError::BodyTooLarge) => return Self::reject(&mut stream, 413),
+				Err(HttpRequestParseError::RequestLineTooLong) => return Self::reject(&mut stream, 414),
+				Err(HttpRequestParseError::HeadersTooLarge) => return Self::reject(&mut stream, 431),
+				Err(HttpRequestParseError::UnsupportedTransferCoding) => return Self::reject(&mut stream, 501),
+
+				// The client hung up part way through the request
+				Err(HttpRequestParseError::ConnectionClosed) | Err(HttpRequestParseError::Io(_)) => return,
+
+				Err(_e) => return Self::reject(&mut stream, 400)
+			};
+
+			handled_requests += 1;
+
+			// Responses to HEAD carry the same headers as they would for GET, Content-Length included, but no content
+			let include_body = request.method != HttpMethod::HEAD;
+
+			let mut keep_alive = request.wants_keep_alive() && handled_requests < max_requests;
+
+			// A panicking route callback gets the client a 500 rather than a connection closed without any response. The
+			// callback may have left shared state half updated, so the connection isn't reused.
+			let mut response = match panic::catch_unwind(AssertUnwindSafe(|| self.handle_request(request))) {
+				Ok(response) => response,
+				Err(_panic) => HttpResponse::new().status(500).header(String::from("Connection"), String::from("close"))
+			};
+
+			// Shutdown may have been requested while the callback ran, in which case this is the connection's last response
+			keep_alive = keep_alive && !self.shutdown.is_shutdown_requested();
+
+			// The route callback may also ask for the connection to be closed
+			if let Some(connection) = response.get_header("Connection") {
+				keep_alive = keep_alive && !connection.split(',').any(|option| option.trim().eq_ignore_ascii_case("close"));
+			}
+
+			response = match keep_alive {
+				true => response.header(
+					String::from("Keep-Alive"),
+					format!("timeout={}, max={}", self.options.keep_alive_timeout.as_secs(), max_requests - handled_requests)
+				),
+				false => response.header(String::from("Connection"), String::from("close"))
+			};
+
+			// The client may have gone away - there is nobody left to report a failed write to
+			if response.write(&mut stream, include_body).is_err() || !keep_alive {
+				return;
 			}
 		}
+	}
 
-		if request.method == HttpMethod::GET {
-			for static_directory in &self.static_directories {
-				let path = Path::new(&static_directory).join(&request.uri.path.strip_prefix("/").unwrap_or(&request.uri.path));
+	/// Sends an error response to a client whose connection can't be used for any further requests
+	fn reject(stream: &mut TcpStream, status: u16) -> () {
+		let response = HttpResponse::new()
+			.status(status)
+			.header(String::from("Connection"), String::from("close"));
 
-				// TODO: Support index files
-				if path.exists() && path.is_file() {
-					let content_or_error = fs::read_to_string(path);
+		// The client may have gone away - there is nobody left to report a failed write to
+		let _ = response.write_to(stream);
+	}
+
+	/// Finds the first route matching the request. HEAD requests fall back to the GET route for the path, since a HEAD
+	/// response is the GET response without its content (RFC 9110 section 9.3.2).
+	fn find_route(&self, request: &HttpRequest) -> Option<(&HttpRoute<S>, HashMap<String, String>)> {
+		let path = &request.uri.path;
+
+		return match request.method {
+			HttpMethod::HEAD => self.router.find(&HttpMethod::HEAD, path).or_else(|| self.router.find(&HttpMethod::GET, path)),
+			_ => self.router.find(&request.method, path)
+		};
+	}
+
+	fn handle_request(&self, mut request: HttpRequest) -> HttpResponse {
+		// Find the matching route and return the result of the callback
+		if let Some((route, params)) = self.find_route(&request) {
+			request.params = params;
+
+			return (route.callback)(request.with_state(Arc::clone(&self.state)));
+		}
 
-					return match content_or_error {
-						Ok(content) => HttpResponse::new()
-							.status(200)
-							.header(String::from("Content-Type"), MimeType::from_file_path(request.uri.path))
-							.content(content),
-						Err(_e) => HttpResponse::new().status(500)
+		if request.method == HttpMethod::GET || request.method == HttpMethod::HEAD {
+			for static_mount in &self.static_mounts {
+				if let Some(response) = static_mount.respond(&request, &self.mime_types) {
+					return response;
+				}
+			}
+
+			// Only once no directory has the file can a single-page application's fallback page take over, and never for a
+			// path which routes handle with other methods since that deserves a 405
+			if self.router.allowed_methods(&request.uri.path).is_empty() {
+				for static_mount in &self.static_mounts {
+					if let Some(response) = static_mount.respond_with_fallback(&request, &self.mime_types) {
+						return response;
 					}
 				}
 			}
 		}
 
-		// If there are no matching routes - return a 404 error
-		return HttpResponse::new().status(404);
+		let allowed_methods = self.allowed_methods(&request);
+
+		// If nothing at all is available at the path - return a 404 error
+		if allowed_methods.is_empty() {
+			return HttpResponse::new().status(404);
+		}
+
+		let allow = allowed_methods.iter().map(HttpMethod::to_string).collect::<Vec<String>>().join(", ");
+
+		// OPTIONS is answered from the registered routes unless a route handles it itself
+		return match request.method {
+			HttpMethod::OPTIONS => HttpResponse::new().status(204).header(String::from("Allow"), allow),
+			_ => HttpResponse::new().status(405).header(String::from("Allow"), allow)
+		};
+	}
+
+	/// The methods which something is registered to handle at the request's path, for the Allow header (RFC 9110 section
+	/// 10.2.1). The path "*" (as in `OPTIONS *`) refers to the server as a whole. Empty if nothing exists at the path.
+	fn allowed_methods(&self, request: &HttpRequest) -> Vec<HttpMethod> {
+		let is_server_wide = request.uri.path == "*";
+
+		let mut allowed_methods: Vec<HttpMethod> = Vec::new();
+
+		let route_methods = match is_server_wide {
+			true => self.router.routes().iter().map(|route| route.method.clone()).collect(),
+			false => self.router.allowed_methods(&request.uri.path)
+		};
+
+		for method in route_methods {
+			if !allowed_methods.contains(&method) {
+				allowed_methods.push(method);
+			}
+		}
+
+		let serves_static_files = match is_server_wide {
+			true => !self.static_mounts.is_empty(),
+			false => self.static_mounts.iter().any(|static_mount| static_mount.serves(&request.uri.path))
+		};
+
+		if serves_static_files && !allowed_methods.contains(&HttpMethod::GET) {
+			allowed_methods.push(HttpMethod::GET);
+		}
+
+		if allowed_methods.is_empty() && !is_server_wide {
+			return allowed_methods;
+		}
+
+		if allowed_methods.contains(&HttpMethod::GET) && !allowed_methods.contains(&HttpMethod::HEAD) {
+			allowed_methods.push(HttpMethod::HEAD);
+		}
+
+		if !allowed_methods.contains(&HttpMethod::OPTIONS) {
+			allowed_methods.push(HttpMethod::OPTIONS);
+		}
+
+		return allowed_methods;
 	}
 }
@@ -0,0 +1,161 @@
+use std::{
+	collections::HashMap,
+	net::{
+		IpAddr,
+		Ipv4Addr,
+		Ipv6Addr,
+		Shutdown,
+		SocketAddr,
+		TcpStream
+	},
+	sync::{
+		Arc,
+		Mutex
+	},
+	time::{
+		Duration,
+		Instant
+	},
+};
+
+/// Allows a running `HttpServer` to be stopped from another thread. Once shutdown is requested the server stops
+/// accepting connections, closes idle persistent connections, lets in-flight requests finish until the grace period
+/// expires and then forcibly closes whatever connections remain before `listen` returns.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+	state: Arc<Mutex<ShutdownState>>
+}
+
+struct ShutdownState {
+	deadline: Option<Instant>,
+	listener_address: Option<SocketAddr>,
+	connections: HashMap<u64, TrackedConnection>,
+	next_connection_id: u64
+}
+
+struct TrackedConnection {
+	stream: TcpStream,
+	idle: bool
+}
+
+/// Registration of a connection with its server's shutdown handle, removed again when dropped
+pub(crate) struct TrackedConnectionGuard {
+	state: Arc<Mutex<ShutdownState>>,
+	id: u64
+}
+
+impl ShutdownHandle {
+	pub(crate) fn new() -> Self {
+		return Self {
+			state: Arc::new(Mutex::new(ShutdownState {
+				deadline: Option::None,
+				listener_address: Option::None,
+				connections: HashMap::new(),
+				next_connection_id: 0
+			}))
+		};
+	}
+
+	/// Requests that the server shuts down, giving in-flight requests up to `grace_period` to complete. Calling this
+	/// more than once keeps the earliest deadline.
+	pub fn shutdown(&self, grace_period: Duration) -> () {
+		let listener_address = {
+			let mut state = self.state.lock().unwrap();
+
+			let deadline = Instant::now() + grace_period;
+
+			state.deadline = Some(state.deadline.map_or(deadline, |existing| existing.min(deadline)));
+
+			// Connections waiting for their next request have nothing in flight and can be closed straight away
+			for connection in state.connections.values().filter(|connection| connection.idle) {
+				let _ = connection.stream.shutdown(Shutdown::Read);
+			}
+
+			state.listener_address
+		};
+
+		// The accept loop blocks until a client connects so we connect to it ourselves to wake it up
+		if let Some(mut address) = listener_address {
+			if address.ip().is_unspecified() {
+				address.set_ip(match address.ip() {
+					IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+					IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST)
+				});
+			}
+
+			let _ = TcpStream::connect_timeout(&address, Duration::from_secs(1));
+		}
+	}
+
+	pub fn is_shutdown_requested(&self) -> bool {
+		return self.state.lock().unwrap().deadline.is_some();
+	}
+
+	pub(crate) fn deadline(&self) -> Option<Instant> {
+		return self.state.lock().unwrap().deadline;
+	}
+
+	/// Records the address being listened on so that a later shutdown can wake the accept loop. Returns whether
+	/// shutdown has already been requested, in which case the server shouldn't start accepting connections at all.
+	pub(crate) fn set_listener_address(&self, address: SocketAddr) -> bool {
+		let mut state = self.state.lock().unwrap();
+
+		state.listener_address = Some(address);
+
+		return state.deadline.is_some();
+	}
+
+	/// Starts tracking a connection so that it can be closed on shutdown. Connections picked up after the shutdown
+	/// deadline has passed are not tracked and should be dropped.
+	pub(crate) fn track(&self, stream: &TcpStream) -> Option<TrackedConnectionGuard> {
+		let stream = match stream.try_clone() {
+			Ok(stream) => stream,
+			Err(_e) => return Option::None
+		};
+
+		let mut state = self.state.lock().unwrap();
+
+		if state.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+			return Option::None;
+		}
+
+		let id = state.next_connection_id;
+
+		state.next_connection_id += 1;
+		state.connections.insert(id, TrackedConnection { stream, idle: false });
+
+		return Some(TrackedConnectionGuard {
+			state: Arc::clone(&self.state),
+			id
+		});
+	}
+
+	/// Forcibly closes every connection still open, unblocking any worker reading from or writing to them
+	pub(crate) fn close_connections(&self) -> () {
+		for connection in self.state.lock().unwrap().connections.values() {
+			let _ = connection.stream.shutdown(Shutdown::Both);
+		}
+	}
+}
+
+impl TrackedConnectionGuard {
+	/// Marks whether the connection is waiting for its next request. Returns false if the connection should be closed
+	/// instead because the server is shutting down.
+	pub(crate) fn set_idle(&self, idle: bool) -> bool {
+		let mut state = self.state.lock().unwrap();
+
+		let shutting_down = state.deadline.is_some();
+
+		if let Some(connection) = state.connections.get_mut(&self.id) {
+			connection.idle = idle;
+		}
+
+		return !(idle && shutting_down);
+	}
+}
+
+impl Drop for TrackedConnectionGuard {
+	fn drop(&mut self) -> () {
+		self.state.lock().unwrap().connections.remove(&self.id);
+	}
+}
@@ -0,0 +1,163 @@
+use std::time::{
+	Duration,
+	SystemTime,
+	UNIX_EPOCH
+};
+
+use crate::{
+	utils::HttpDate,
+	HttpMethod,
+	HttpRequest,
+};
+
+/// Evaluates If-None-Match and If-Modified-Since (RFC 9110 section 13.2.2) to decide whether the client's cached copy
+/// is still current and a 304 Not Modified can be sent instead of the file
+pub fn is_not_modified(request: &HttpRequest, etag: Option<&str>, modified: Option<SystemTime>) -> bool {
+	if request.method != HttpMethod::GET && request.method != HttpMethod::HEAD {
+		return false;
+	}
+
+	// If-Modified-Since is only a fallback for clients which don't have an entity tag to send
+	if let Some(if_none_match) = request.header("If-None-Match") {
+		let if_none_match = if_none_match.to_string();
+
+		// "*" matches any current representation, and there is one since the file is being served
+		if if_none_match.trim() == "*" {
+			return true;
+		}
+
+		return match etag {
+			Some(etag) => parse_entity_tags(&if_none_match).iter().any(|candidate| weak_compare(candidate, etag)),
+			None => false
+		};
+	}
+
+	let if_modified_since = match request.header("If-Modified-Since").and_then(|date| HttpDate::parse(&date.to_string())) {
+		Some(if_modified_since) => if_modified_since,
+		None => return false
+	};
+
+	return match modified {
+		// HTTP-dates only have a resolution of a second
+		Some(modified) => truncate_to_seconds(modified) <= if_modified_since,
+		None => false
+	};
+}
+
+/// Evaluates If-Range (RFC 9110 section 13.1.5) to decide whether a Range header still applies to the current version
+/// of the file. Without If-Range the ranges always apply.
+pub fn is_range_current(request: &HttpRequest, etag: Option<&str>, modified: Option<SystemTime>) -> bool {
+	let if_range = match request.header("If-Range") {
+		Some(if_range) => if_range.to_string(),
+		None => return true
+	};
+
+	let if_range = if_range.trim();
+
+	if if_range.starts_with('"') || if_range.starts_with("W/") {
+		return match etag {
+			Some(etag) => strong_compare(if_range, etag),
+			None => false
+		};
+	}
+
+	// A date only validates the ranges if it is exactly the file's modification time
+	return match (HttpDate::parse(if_range), modified) {
+		(Some(if_range), Some(modified)) => truncate_to_seconds(modified) == if_range,
+		_ => false
+	};
+}
+
+/// Two entity tags only match under strong comparison if neither is weak and their opaque tags are equal
+pub fn strong_compare(a: &str, b: &str) -> bool {
+	return !a.starts_with("W/") && !b.starts_with("W/") && a == b;
+}
+
+/// Two entity tags match under weak comparison if their opaque tags are equal, whether or not either is weak
+pub fn weak_compare(a: &str, b: &str) -> bool {
+	return a.trim_start_matches("W/") == b.trim_start_matches("W/");
+}
+
+/// Splits a list of entity tags such as `W/"a", "b,c"`. Commas are allowed inside the quotes so the list can't simply be
+/// split on them.
+pub fn parse_entity_tags(list: &str) -> Vec<String> {
+	let mut entity_tags = Vec::new();
+	let mut remaining = list.trim_start_matches([' ', '\t', ',']);
+
+	while !remaining.is_empty() {
+		let (prefix, after_prefix) = match remaining.strip_prefix("W/") {
+			Some(after_prefix) => ("W/", after_prefix),
+			None => ("", remaining)
+		};
+
+		let closing_quote = match after_prefix.strip_prefix('"').and_then(|tag| tag.find('"')) {
+			Some(closing_quote) => closing_quote,
+			None => break
+		};
+
+		let (opaque_tag, rest) = after_prefix.split_at(closing_quote + 2);
+
+		entity_tags.push(format!("{}{}", prefix, opaque_tag));
+
+		remaining = rest.trim_start_matches([' ', '\t', ',']);
+	}
+
+	return entity_tags;
+}
+
+pub fn truncate_to_seconds(time: SystemTime) -> SystemTime {
+	return match time.duration_since(UNIX_EPOCH) {
+		Ok(since_epoch) => UNIX_EPOCH + Duration::from_secs(since_epoch.as_secs()),
+		Err(_e) => time
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		io::Cursor,
+		time::{
+			Duration,
+			UNIX_EPOCH
+		},
+	};
+
+	use crate::{
+		HttpRequest,
+		HttpServerOptions,
+	};
+
+	use super::is_not_modified;
+
+	fn request(method: &str, header: &str) -> HttpRequest {
+		let raw_request = format!("{} /file.txt HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n", method, header);
+
+		return HttpRequest::from_stream(&mut Cursor::new(raw_request), &HttpServerOptions::default()).unwrap();
+	}
+
+	#[test]
+	fn if_none_match_star_matches_without_an_etag() {
+		assert!(is_not_modified(&request("GET", "If-None-Match: *"), Option::None, Option::None));
+		assert!(is_not_modified(&request("HEAD", "If-None-Match: *"), Some("\"abc\""), Option::None));
+		assert!(!is_not_modified(&request("POST", "If-None-Match: *"), Option::None, Option::None));
+	}
+
+	#[test]
+	fn if_none_match_compares_entity_tags_weakly() {
+		assert!(is_not_modified(&request("GET", "If-None-Match: \"x\", W/\"abc\""), Some("\"abc\""), Option::None));
+		assert!(!is_not_modified(&request("GET", "If-None-Match: \"x\""), Some("\"abc\""), Option::None));
+		assert!(!is_not_modified(&request("GET", "If-None-Match: \"abc\""), Option::None, Option::None));
+	}
+
+	#[test]
+	fn if_modified_since_is_ignored_when_if_none_match_is_sent() {
+		let modified = Some(UNIX_EPOCH + Duration::from_secs(784111777));
+
+		assert!(is_not_modified(&request("GET", "If-Modified-Since: Sun, 06 Nov 1994 08:49:37 GMT"), Option::None, modified));
+		assert!(!is_not_modified(&request("GET", "If-Modified-Since: Sun, 06 Nov 1994 08:49:36 GMT"), Option::None, modified));
+
+		let both = "If-None-Match: \"x\"\r\nIf-Modified-Since: Sun, 06 Nov 1994 08:49:37 GMT";
+
+		assert!(!is_not_modified(&request("GET", both), Some("\"abc\""), modified));
+	}
+}
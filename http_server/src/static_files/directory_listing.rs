@@ -0,0 +1,148 @@
+use std::{
+	fs,
+	path::Path,
+	time::SystemTime,
+};
+
+use crate::{
+	utils::{
+		HttpDate,
+		URLEncoding
+	},
+	HttpRequest,
+	HttpResponse,
+};
+
+use super::negotiation;
+
+struct DirectoryEntry {
+	name: String,
+	is_directory: bool,
+	size: u64,
+	modified: Option<SystemTime>
+}
+
+/// Renders the contents of a directory for a request whose path ended in a slash. `display_path` is the decoded path
+/// of the directory shown to the user.
+pub fn render(directory: &Path, display_path: &str, show_dotfiles: bool, request: &HttpRequest) -> HttpResponse {
+	let entries = match read_entries(directory, show_dotfiles) {
+		Ok(entries) => entries,
+		Err(_e) => return HttpResponse::new().status(500)
+	};
+
+	let prefers_json = negotiation::media_type_quality(request, "application/json") > negotiation::media_type_quality(request, "text/html");
+
+	// Both formats live at the same URL, so caches must keep them apart by the Accept header that chose between them
+	let response = HttpResponse::new()
+		.status(200)
+		.header(String::from("Vary"), String::from("Accept"));
+
+	return match prefers_json {
+		true => response
+			.header(String::from("Content-Type"), String::from("application/json"))
+			.content(render_json(&entries)),
+		false => response.html(render_html(&entries, display_path))
+	};
+}
+
+fn read_entries(directory: &Path, show_dotfiles: bool) -> std::io::Result<Vec<DirectoryEntry>> {
+	let mut entries = Vec::new();
+
+	for entry in fs::read_dir(directory)? {
+		let entry = entry?;
+
+		let name = entry.file_name().to_string_lossy().to_string();
+
+		if name.starts_with('.') && !show_dotfiles {
+			continue;
+		}
+
+		// Follow symlinks so that they are listed as whatever they point to. Broken links are skipped.
+		let metadata = match fs::metadata(entry.path()) {
+			Ok(metadata) => metadata,
+			Err(_e) => continue
+		};
+
+		entries.push(DirectoryEntry {
+			name,
+			is_directory: metadata.is_dir(),
+			size: metadata.len(),
+			modified: metadata.modified().ok()
+		});
+	}
+
+	// Directories first, then alphabetically
+	entries.sort_by(|a, b| b.is_directory.cmp(&a.is_directory).then_with(|| a.name.cmp(&b.name)));
+
+	return Ok(entries);
+}
+
+fn render_html(entries: &[DirectoryEntry], display_path: &str) -> String {
+	let title = escape_html(&format!("Index of {}", display_path));
+
+	let mut rows = String::new();
+
+	if display_path != "/" {
+		rows.push_str("<tr><td><a href=\"../\">../</a></td><td></td><td></td></tr>\n");
+	}
+
+	for entry in entries {
+		let suffix = if entry.is_directory { "/" } else { "" };
+
+		let size = match entry.is_directory {
+			true => String::from("-"),
+			false => entry.size.to_string()
+		};
+
+		rows.push_str(&format!(
+			"<tr><td><a href=\"{}{}\">{}{}</a></td><td>{}</td><td>{}</td></tr>\n",
+			URLEncoding::encode_path_segment(&entry.name),
+			suffix,
+			escape_html(&entry.name),
+			suffix,
+			size,
+			entry.modified.map(HttpDate::format).unwrap_or_default()
+		));
+	}
+
+	return format!(
+		"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n<table>\n<thead><tr><th>Name</th><th>Size</th><th>Last modified</th></tr></thead>\n<tbody>\n{rows}</tbody>\n</table>\n</body>\n</html>\n"
+	);
+}
+
+fn render_json(entries: &[DirectoryEntry]) -> String {
+	let entries = entries
+		.iter()
+		.map(|entry| format!(
+			"{{\"name\":\"{}\",\"type\":\"{}\",\"size\":{},\"modified\":{}}}",
+			escape_json(&entry.name),
+			if entry.is_directory { "directory" } else { "file" },
+			entry.size,
+			entry.modified.map_or(String::from("null"), |modified| format!("\"{}\"", HttpDate::format(modified)))
+		))
+		.collect::<Vec<String>>()
+		.join(",");
+
+	return format!("[{}]", entries);
+}
+
+fn escape_html(text: &str) -> String {
+	return text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&#39;");
+}
+
+fn escape_json(text: &str) -> String {
+	return text
+		.chars()
+		.map(|char| match char {
+			'"' => String::from("\\\""),
+			'\\' => String::from("\\\\"),
+			char if (char as u32) < 0x20 => format!("\\u{:04x}", char as u32),
+			char => char.to_string()
+		})
+		.collect::<String>();
+}
@@ -0,0 +1,126 @@
+use std::{
+	env,
+	fmt::Write as FmtWrite,
+	fs,
+	io::{
+		Error as IoError,
+		ErrorKind,
+		Result as IoResult
+	},
+	path::{
+		Path,
+		PathBuf
+	},
+	time::{
+		Duration,
+		UNIX_EPOCH
+	},
+};
+
+/// A file embedded into the binary by `embed_directory`
+pub struct EmbeddedFile {
+	/// Path relative to the embedded directory, using forward slashes
+	pub path: &'static str,
+	pub content: &'static [u8],
+	/// Modification time of the file when it was embedded, as the time since the Unix epoch
+	pub modified: Option<Duration>
+}
+
+/// Includes the table of files written by `embed_directory` from a build script, for passing to
+/// `HttpServer::serve_embedded`:
+///
+/// ```ignore
+/// server.serve_embedded(http_server::include_embedded!("public.rs"));
+/// ```
+#[macro_export]
+macro_rules! include_embedded {
+	($output_name:literal) => {{
+		use $crate::EmbeddedFile;
+
+		// A constant so that the table, which calls functions to build it, can still be borrowed for 'static
+		const EMBEDDED_FILES: &[EmbeddedFile] = include!(concat!(env!("OUT_DIR"), "/", $output_name));
+
+		EMBEDDED_FILES
+	}};
+}
+
+/// Generates a table embedding every file in `source_directory` into the binary, to be called from a build script:
+///
+/// ```ignore
+/// fn main() {
+///     http_server::embed_directory("public", "public.rs").unwrap();
+/// }
+/// ```
+///
+/// The table is written to `output_name` in Cargo's OUT_DIR and included with `include_embedded!`. A relative
+/// `source_directory` is taken relative to the package being built. Cargo is told to rerun the build script whenever
+/// anything in the directory changes.
+pub fn embed_directory(source_directory: impl AsRef<Path>, output_name: &str) -> IoResult<()> {
+	let out_dir = env::var_os("OUT_DIR").ok_or_else(|| IoError::new(ErrorKind::NotFound, "OUT_DIR is not set"))?;
+	let manifest_dir = env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+
+	let source_directory = fs::canonicalize(manifest_dir.join(source_directory))?;
+
+	let mut files = Vec::new();
+
+	collect_files(&source_directory, &mut Vec::new(), &mut files)?;
+
+	files.sort();
+
+	let mut table = String::from("&[\n");
+
+	for (relative_path, path) in files {
+		let path = path.to_str().ok_or_else(|| IoError::new(ErrorKind::InvalidData, "file path is not valid UTF-8"))?;
+
+		let modified = match fs::metadata(path)?.modified().ok().and_then(|modified| modified.duration_since(UNIX_EPOCH).ok()) {
+			Some(modified) => format!("Some(::std::time::Duration::new({}, {}))", modified.as_secs(), modified.subsec_nanos()),
+			None => String::from("None")
+		};
+
+		// Debug formatting produces valid Rust string literals, escapes included
+		let _ = writeln!(
+			table,
+			"\tEmbeddedFile {{ path: {:?}, content: include_bytes!({:?}), modified: {} }},",
+			relative_path,
+			path,
+			modified
+		);
+	}
+
+	table.push(']');
+
+	fs::write(Path::new(&out_dir).join(output_name), table)?;
+
+	println!("cargo:rerun-if-changed={}", source_directory.display());
+
+	return Ok(());
+}
+
+fn collect_files(directory: &Path, relative_segments: &mut Vec<String>, files: &mut Vec<(String, PathBuf)>) -> IoResult<()> {
+	// Adding or removing a file only changes the modification time of the directory it is in
+	println!("cargo:rerun-if-changed={}", directory.display());
+
+	for entry in fs::read_dir(directory)? {
+		let entry = entry?;
+		let path = entry.path();
+
+		let name = entry
+			.file_name()
+			.into_string()
+			.map_err(|_name| IoError::new(ErrorKind::InvalidData, "file name is not valid UTF-8"))?;
+
+		relative_segments.push(name);
+
+		if path.is_dir() {
+			collect_files(&path, relative_segments, files)?;
+		} else if path.is_file() {
+			println!("cargo:rerun-if-changed={}", path.display());
+
+			files.push((relative_segments.join("/"), path));
+		}
+
+		relative_segments.pop();
+	}
+
+	return Ok(());
+}
@@ -0,0 +1,115 @@
+use std::{
+	collections::HashMap,
+	io::{
+		Cursor,
+		Result as IoResult
+	},
+	time::UNIX_EPOCH,
+};
+
+use crate::{
+	HttpRequest,
+	HttpResponse,
+};
+
+use super::{
+	embedded::EmbeddedFile,
+	path::{
+		StaticPath,
+		StaticPathError
+	},
+	static_mount::{
+		OpenedFile,
+		StaticEntry,
+		StaticSource
+	},
+	StaticOptions,
+};
+
+/// Files embedded into the binary, served below a URL prefix just like a directory on disk. Directory listings aren't
+/// available since there is no directory to list.
+pub struct EmbeddedDirectory {
+	url_prefix: Vec<String>,
+	files: HashMap<&'static str, &'static EmbeddedFile>,
+	options: StaticOptions
+}
+
+impl EmbeddedDirectory {
+	pub fn new(url_prefix: &str, files: &'static [EmbeddedFile], options: StaticOptions) -> Self {
+		return Self {
+			url_prefix: StaticPath::prefix_segments(url_prefix),
+			files: files.iter().map(|file| (file.path, file)).collect(),
+			options
+		};
+	}
+
+	fn is_directory(&self, path: &str) -> bool {
+		let directory_prefix = format!("{}/", path);
+
+		return path.is_empty() || self.files.keys().any(|file_path| file_path.starts_with(&directory_prefix));
+	}
+
+	fn child_path(path: &str, child: &str) -> String {
+		return match path.is_empty() {
+			true => String::from(child),
+			false => format!("{}/{}", path, child)
+		};
+	}
+}
+
+impl StaticSource for EmbeddedDirectory {
+	type File = &'static EmbeddedFile;
+	/// Path of the directory relative to the embedded directory, empty for the embedded directory itself
+	type Directory = String;
+
+	const LISTS_DIRECTORIES: bool = false;
+
+	fn url_prefix(&self) -> &[String] {
+		return &self.url_prefix;
+	}
+
+	fn options(&self) -> &StaticOptions {
+		return &self.options;
+	}
+
+	fn find(&self, path: &StaticPath) -> Result<StaticEntry<&'static EmbeddedFile, String>, StaticPathError> {
+		let path = path.segments.join("/");
+
+		if let Some(file) = self.files.get(path.as_str()) {
+			return Ok(StaticEntry::File(file));
+		}
+
+		return match self.is_directory(&path) {
+			true => Ok(StaticEntry::Directory(path)),
+			false => Err(StaticPathError::NotFound)
+		};
+	}
+
+	fn find_child(&self, directory: &String, name: &str) -> Option<&'static EmbeddedFile> {
+		return self.files.get(Self::child_path(directory, name).as_str()).copied();
+	}
+
+	fn find_relative(&self, relative_path: &str) -> Option<&'static EmbeddedFile> {
+		return self.files.get(relative_path.trim_start_matches('/')).copied();
+	}
+
+	fn find_sibling(&self, file: &&'static EmbeddedFile, suffix: &str) -> Option<&'static EmbeddedFile> {
+		return self.files.get(format!("{}{}", file.path, suffix).as_str()).copied();
+	}
+
+	fn file_name(&self, file: &&'static EmbeddedFile) -> String {
+		return String::from(file.path);
+	}
+
+	fn open(&self, file: &&'static EmbeddedFile) -> IoResult<OpenedFile> {
+		return Ok(OpenedFile {
+			content: Box::new(Cursor::new(file.content)),
+			length: file.content.len() as u64,
+			modified: file.modified.map(|modified| UNIX_EPOCH + modified)
+		});
+	}
+
+	fn list(&self, _request: &HttpRequest, _directory: &String, _display_path: &str, _show_dotfiles: bool) -> HttpResponse {
+		return HttpResponse::new().status(404);
+	}
+}
@@ -0,0 +1,17 @@
+mod conditional;
+mod directory_listing;
+mod embedded;
+mod embedded_directory;
+mod negotiation;
+mod path;
+mod range;
+mod static_directory;
+mod static_file;
+mod static_mount;
+mod static_options;
+
+pub use embedded::{embed_directory, EmbeddedFile};
+pub use embedded_directory::EmbeddedDirectory;
+pub use static_directory::StaticDirectory;
+pub use static_mount::StaticMount;
+pub use static_options::{DotfilePolicy, EntityTagMode, StaticOptions};
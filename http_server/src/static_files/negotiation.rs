@@ -0,0 +1,142 @@
+use crate::{
+	request::HttpHeaderValue,
+	HttpRequest,
+};
+
+/// The quality (between 0 and 1) the client assigned to a media type in its Accept header, using the most specific
+/// matching range as described in RFC 9110 section 12.5.1. A request without an Accept header accepts everything.
+pub fn media_type_quality(request: &HttpRequest, media_type: &str) -> f32 {
+	let accept = match request.header("Accept") {
+		Some(accept) => accept,
+		None => return 1.0
+	};
+
+	let (media_type_main, _subtype) = media_type.split_once('/').unwrap_or((media_type, ""));
+
+	let mut best_match: Option<(u8, f32)> = Option::None;
+
+	for element in accept.as_list() {
+		let range = HttpHeaderValue::new(element).as_parameterized_header_value();
+
+		let specificity = match range.value.split_once('/') {
+			_ if range.value.eq_ignore_ascii_case(media_type) => 2,
+			Some((main, "*")) if main.eq_ignore_ascii_case(media_type_main) => 1,
+			Some(("*", "*")) => 0,
+			_ => continue
+		};
+
+		if best_match.is_none_or(|(best_specificity, _quality)| specificity > best_specificity) {
+			best_match = Some((specificity, parse_quality(range.parameters.get("q"))));
+		}
+	}
+
+	return best_match.map_or(0.0, |(_specificity, quality)| quality);
+}
+
+fn parse_quality(quality: Option<&String>) -> f32 {
+	return quality
+		.and_then(|quality| quality.parse::<f32>().ok())
+		.map_or(1.0, |quality| quality.clamp(0.0, 1.0));
+}
+
+/// The quality the client assigned to a content coding in its Accept-Encoding header (RFC 9110 section 12.5.3). A
+/// request without an Accept-Encoding header is only assumed to accept the identity coding.
+pub fn content_coding_quality(request: &HttpRequest, coding: &str) -> f32 {
+	let accept_encoding = match request.header("Accept-Encoding") {
+		Some(accept_encoding) => accept_encoding,
+		None => return 0.0
+	};
+
+	let mut wildcard_quality = Option::None;
+
+	for element in accept_encoding.as_list() {
+		let element = HttpHeaderValue::new(element).as_parameterized_header_value();
+		// "x-gzip" is an old alias of "gzip" which some clients still send
+		let element_coding = element.value.strip_prefix("x-").unwrap_or(&element.value);
+
+		if element_coding.eq_ignore_ascii_case(coding) {
+			return parse_quality(element.parameters.get("q"));
+		}
+
+		if element.value == "*" {
+			wildcard_quality = Some(parse_quality(element.parameters.get("q")));
+		}
+	}
+
+	return wildcard_quality.unwrap_or(0.0);
+}
+
+/// Picks the content coding the client prefers out of those available, favouring the earliest on a tie. Returns `None`
+/// if the client accepts none of them.
+pub fn preferred_content_coding<'a>(request: &HttpRequest, available: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+	return available
+		.into_iter()
+		.map(|coding| (content_coding_quality(request, coding), coding))
+		.filter(|(quality, _coding)| *quality > 0.0)
+		.reduce(|best, candidate| match candidate.0 > best.0 {
+			true => candidate,
+			false => best
+		})
+		.map(|(_quality, coding)| coding);
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use crate::{
+		HttpRequest,
+		HttpServerOptions,
+	};
+
+	use super::{
+		content_coding_quality,
+		media_type_quality,
+		preferred_content_coding,
+	};
+
+	fn request(header: &str) -> HttpRequest {
+		let raw_request = format!("GET / HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n", header);
+
+		return HttpRequest::from_stream(&mut Cursor::new(raw_request), &HttpServerOptions::default()).unwrap();
+	}
+
+	#[test]
+	fn reads_qualities() {
+		let request = request("Accept-Encoding: gzip;q=0.5, br, identity;q=\"0.2\", *;q=0.1");
+
+		assert_eq!(content_coding_quality(&request, "gzip"), 0.5);
+		assert_eq!(content_coding_quality(&request, "br"), 1.0);
+		assert_eq!(content_coding_quality(&request, "identity"), 0.2);
+		assert_eq!(content_coding_quality(&request, "zstd"), 0.1);
+		assert_eq!(preferred_content_coding(&request, ["gzip", "br"]), Some("br"));
+	}
+
+	#[test]
+	fn malformed_qualities_count_as_the_default() {
+		for quality in ["\"", "\"\"", "abc", ""] {
+			let accept_encoding = request(&format!("Accept-Encoding: br;q={}", quality));
+			let accept = request(&format!("Accept: text/html;q={}", quality));
+
+			assert_eq!(content_coding_quality(&accept_encoding, "br"), 1.0, "{:?}", quality);
+			assert_eq!(media_type_quality(&accept, "text/html"), 1.0, "{:?}", quality);
+		}
+
+		assert_eq!(content_coding_quality(&request("Accept-Encoding: br;q=\"; gzip"), "br"), 1.0);
+	}
+
+	#[test]
+	fn qualities_are_clamped() {
+		assert_eq!(content_coding_quality(&request("Accept-Encoding: br;q=5"), "br"), 1.0);
+		assert_eq!(media_type_quality(&request("Accept: text/html;q=-1"), "text/html"), 0.0);
+	}
+
+	#[test]
+	fn most_specific_media_range_wins() {
+		let request = request("Accept: */*;q=0.1, text/*;q=0.5, text/html;q=0");
+
+		assert_eq!(media_type_quality(&request, "text/html"), 0.0);
+		assert_eq!(media_type_quality(&request, "text/plain"), 0.5);
+		assert_eq!(media_type_quality(&request, "image/png"), 0.1);
+	}
+}
@@ -0,0 +1,278 @@
+use std::{
+	fs,
+	path::{
+		Path,
+		PathBuf
+	},
+};
+
+use crate::utils::URLEncoding;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StaticPathError {
+	/// The path tries to reach outside of the static directory or contains characters that could be used to do so
+	Forbidden,
+	NotFound
+}
+
+/// A request path which has been normalized and decoded into segments that are safe to look up inside a static
+/// directory
+pub struct StaticPath {
+	pub segments: Vec<String>,
+
+	/// Whether the path refers to a directory, i.e. it ended with a slash or a dot segment
+	pub trailing_slash: bool
+}
+
+impl StaticPath {
+	/// Removes dot segments as described in RFC 3986 section 5.2.4 and then percent-decodes each remaining segment.
+	/// Decoding happens after dot segment removal so that encoded dots and separators (e.g. "%2e%2e" or "%2f") can't
+	/// sneak a traversal past it - any segment which decodes to something special is rejected instead.
+	pub fn parse(request_path: &str) -> Result<Self, StaticPathError> {
+		let mut segments: Vec<&str> = Vec::new();
+		let mut trailing_slash = false;
+
+		for segment in request_path.split('/') {
+			trailing_slash = matches!(segment, "" | "." | "..");
+
+			match segment {
+				// Empty segments come from the leading slash or repeated slashes and don't change the path
+				"" | "." => (),
+				// Going above the root is clamped to the root rather than being an error, as RFC 3986 specifies
+				".." => {
+					segments.pop();
+				},
+				segment => segments.push(segment)
+			}
+		}
+
+		let segments = segments
+			.into_iter()
+			.map(Self::decode_segment)
+			.collect::<Result<Vec<String>, StaticPathError>>()?;
+
+		return Ok(Self {
+			segments,
+			trailing_slash
+		});
+	}
+
+	/// Splits a URL prefix such as "/assets/" into the segments it matches
+	pub fn prefix_segments(url_prefix: &str) -> Vec<String> {
+		return url_prefix
+			.split('/')
+			.filter(|segment| !segment.is_empty())
+			.map(String::from)
+			.collect();
+	}
+
+	/// Returns the remainder of the path below `prefix`, or `None` if the path isn't under it
+	pub fn strip_prefix(&self, prefix: &[String]) -> Option<Self> {
+		if !self.segments.starts_with(prefix) {
+			return Option::None;
+		}
+
+		return Some(Self {
+			segments: self.segments[prefix.len()..].to_vec(),
+			trailing_slash: self.trailing_slash
+		});
+	}
+
+	/// The URL of the directory this path refers to, ending with a slash. It is rebuilt from the normalized segments so
+	/// that nothing the client sent (e.g. a leading "//host" which would make it a network-path reference) survives.
+	pub fn directory_url(&self) -> String {
+		let path = self.segments
+			.iter()
+			.map(|segment| format!("/{}", URLEncoding::encode_path_segment(segment)))
+			.collect::<String>();
+
+		return format!("{}/", path);
+	}
+
+	/// Whether any segment of the path names a hidden file or directory
+	pub fn has_dotfile(&self) -> bool {
+		return self.segments.iter().any(|segment| segment.starts_with('.'));
+	}
+
+	/// Finds the file or directory this path refers to inside `root`, making sure that symlinks don't lead outside of it
+	pub fn resolve(&self, root: &Path) -> Result<PathBuf, StaticPathError> {
+		return Self::resolve_segments(root, self.segments.iter().map(String::as_str));
+	}
+
+	/// Finds a file given by a path relative to `root` (e.g. from the server's configuration rather than a request)
+	pub fn resolve_relative(root: &Path, relative_path: &str) -> Result<PathBuf, StaticPathError> {
+		return Self::resolve_segments(root, relative_path.split(['/', '\\']).filter(|segment| !segment.is_empty()));
+	}
+
+	/// Whether the last segment has a file extension, e.g. "app.js" but not "settings" or ".config"
+	pub fn has_extension(&self) -> bool {
+		return match self.segments.last().and_then(|segment| segment.rsplit_once('.')) {
+			Some((stem, extension)) => !stem.is_empty() && !extension.is_empty(),
+			None => false
+		};
+	}
+
+	fn resolve_segments<'a>(root: &Path, segments: impl Iterator<Item = &'a str>) -> Result<PathBuf, StaticPathError> {
+		let root = match fs::canonicalize(root) {
+			Ok(root) => root,
+			Err(_e) => return Err(StaticPathError::NotFound)
+		};
+
+		let path = segments.fold(root.clone(), |path, segment| path.join(segment));
+
+		let path = match fs::canonicalize(path) {
+			Ok(path) => path,
+			Err(_e) => return Err(StaticPathError::NotFound)
+		};
+
+		if !path.starts_with(&root) {
+			return Err(StaticPathError::Forbidden);
+		}
+
+		return Ok(path);
+	}
+
+	fn decode_segment(segment: &str) -> Result<String, StaticPathError> {
+		let decoded = match URLEncoding::percent_decode(segment).map(String::from_utf8) {
+			Some(Ok(decoded)) => decoded,
+			_ => return Err(StaticPathError::NotFound)
+		};
+
+		let is_dot_segment = decoded == "." || decoded == "..";
+		let has_forbidden_character = decoded.contains(['/', '\\', '\0']);
+
+		if is_dot_segment || has_forbidden_character {
+			return Err(StaticPathError::Forbidden);
+		}
+
+		return Ok(decoded);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		env,
+		fs,
+		path::PathBuf,
+		process,
+	};
+
+	use super::{
+		StaticPath,
+		StaticPathError
+	};
+
+	fn segments(request_path: &str) -> Vec<String> {
+		return StaticPath::parse(request_path).unwrap().segments;
+	}
+
+	fn error(request_path: &str) -> StaticPathError {
+		return StaticPath::parse(request_path).err().unwrap();
+	}
+
+	/// A fresh directory for a test to build a static root in
+	fn temporary_directory(name: &str) -> PathBuf {
+		let directory = env::temp_dir().join(format!("http_server-path-{}-{}", name, process::id()));
+
+		let _ = fs::remove_dir_all(&directory);
+		fs::create_dir_all(&directory).unwrap();
+
+		return directory;
+	}
+
+	#[test]
+	fn dot_segments_are_removed() {
+		assert_eq!(segments("/a/./b/../c"), ["a", "c"]);
+		assert_eq!(segments("/a//b/"), ["a", "b"]);
+		assert_eq!(segments("/"), Vec::<String>::new());
+	}
+
+	#[test]
+	fn going_above_the_root_is_clamped() {
+		assert_eq!(segments("/../../etc/passwd"), ["etc", "passwd"]);
+		assert_eq!(segments("/a/../../b"), ["b"]);
+	}
+
+	#[test]
+	fn trailing_slash_is_recorded() {
+		assert!(StaticPath::parse("/a/").unwrap().trailing_slash);
+		assert!(StaticPath::parse("/a/b/..").unwrap().trailing_slash);
+		assert!(StaticPath::parse("/a/.").unwrap().trailing_slash);
+		assert!(!StaticPath::parse("/a").unwrap().trailing_slash);
+	}
+
+	#[test]
+	fn segments_are_percent_decoded() {
+		assert_eq!(segments("/hello%20world/caf%C3%A9"), ["hello world", "café"]);
+	}
+
+	#[test]
+	fn encoded_dot_segments_are_forbidden() {
+		assert_eq!(error("/%2e%2e/etc/passwd"), StaticPathError::Forbidden);
+		assert_eq!(error("/a/%2E%2E/b"), StaticPathError::Forbidden);
+		assert_eq!(error("/a/.%2e/b"), StaticPathError::Forbidden);
+		assert_eq!(error("/a/%2e"), StaticPathError::Forbidden);
+	}
+
+	#[test]
+	fn encoded_separators_and_nul_are_forbidden() {
+		assert_eq!(error("/..%2f..%2fetc/passwd"), StaticPathError::Forbidden);
+		assert_eq!(error("/a%2Fb"), StaticPathError::Forbidden);
+		assert_eq!(error("/..%5c..%5cwindows"), StaticPathError::Forbidden);
+		assert_eq!(error("/file.txt%00.html"), StaticPathError::Forbidden);
+	}
+
+	#[test]
+	fn invalid_encodings_are_not_found() {
+		assert_eq!(error("/%ff%fe"), StaticPathError::NotFound);
+		assert_eq!(error("/%zz"), StaticPathError::NotFound);
+		assert_eq!(error("/trailing%2"), StaticPathError::NotFound);
+	}
+
+	#[test]
+	fn directory_urls_are_rebuilt_from_the_segments() {
+		assert_eq!(StaticPath::parse("//evil.example/../sub").unwrap().directory_url(), "/sub/");
+		assert_eq!(StaticPath::parse("/a%20b/c%3Fd").unwrap().directory_url(), "/a%20b/c%3Fd/");
+		assert_eq!(StaticPath::parse("/").unwrap().directory_url(), "/");
+	}
+
+	#[test]
+	fn files_inside_the_root_resolve() {
+		let root = temporary_directory("inside");
+
+		fs::create_dir(root.join("sub")).unwrap();
+		fs::write(root.join("sub/file.txt"), "content").unwrap();
+
+		let resolved = StaticPath::parse("/sub/../sub/file.txt").unwrap().resolve(&root).unwrap();
+
+		assert_eq!(resolved, fs::canonicalize(root.join("sub/file.txt")).unwrap());
+		assert_eq!(StaticPath::parse("/missing.txt").unwrap().resolve(&root).err(), Some(StaticPathError::NotFound));
+		assert_eq!(StaticPath::resolve_relative(&root, "sub/file.txt").ok(), Some(resolved));
+
+		fs::remove_dir_all(root).unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn symlinks_escaping_the_root_are_forbidden() {
+		let directory = temporary_directory("symlink");
+		let root = directory.join("root");
+		let outside = directory.join("outside");
+
+		fs::create_dir(&root).unwrap();
+		fs::create_dir(&outside).unwrap();
+		fs::write(outside.join("secret.txt"), "secret").unwrap();
+
+		std::os::unix::fs::symlink(&outside, root.join("escape")).unwrap();
+		std::os::unix::fs::symlink(outside.join("secret.txt"), root.join("secret.txt")).unwrap();
+
+		let escape = StaticPath::parse("/escape/secret.txt").unwrap();
+
+		assert_eq!(escape.resolve(&root).err(), Some(StaticPathError::Forbidden));
+		assert_eq!(StaticPath::parse("/secret.txt").unwrap().resolve(&root).err(), Some(StaticPathError::Forbidden));
+		assert_eq!(StaticPath::resolve_relative(&root, "escape/secret.txt").err(), Some(StaticPathError::Forbidden));
+
+		fs::remove_dir_all(directory).unwrap();
+	}
+}
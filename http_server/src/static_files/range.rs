@@ -0,0 +1,329 @@
+use std::{
+	collections::{
+		hash_map::RandomState,
+		VecDeque
+	},
+	hash::{
+		BuildHasher,
+		Hasher
+	},
+	io::{
+		Error as IoError,
+		ErrorKind,
+		Read,
+		Result as IoResult,
+		SeekFrom
+	},
+};
+
+use super::static_file::StaticContent;
+
+/// More ranges than this in one request are more likely to be an attempt to waste resources than a real client, so
+/// the Range header is ignored and the whole file is sent instead
+const MAX_RANGES: usize = 32;
+
+/// An inclusive range of byte positions within a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ByteRange {
+	pub first: u64,
+	pub last: u64
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeRequest {
+	/// The header couldn't be parsed or isn't worth honouring, so it should be ignored
+	Ignored,
+	/// None of the requested ranges overlap the file
+	Unsatisfiable,
+	/// The ranges to send, sorted and with overlapping or adjacent ranges merged
+	Satisfiable(Vec<ByteRange>)
+}
+
+impl ByteRange {
+	pub fn length(&self) -> u64 {
+		return self.last - self.first + 1;
+	}
+
+	pub fn content_range(&self, complete_length: u64) -> String {
+		return format!("bytes {}-{}/{}", self.first, self.last, complete_length);
+	}
+}
+
+/// Parses a Range header (RFC 9110 section 14.1.2) against a file of `complete_length` bytes
+pub fn parse_range_header(range: &str, complete_length: u64) -> RangeRequest {
+	let range_set = match range.trim().split_once('=') {
+		Some((unit, range_set)) if unit.trim().eq_ignore_ascii_case("bytes") => range_set,
+		_ => return RangeRequest::Ignored
+	};
+
+	let specs = range_set
+		.split(',')
+		.map(str::trim)
+		.filter(|spec| !spec.is_empty())
+		.collect::<Vec<&str>>();
+
+	if specs.is_empty() || specs.len() > MAX_RANGES {
+		return RangeRequest::Ignored;
+	}
+
+	let mut ranges = Vec::new();
+
+	for spec in specs {
+		let (first, last) = match spec.split_once('-') {
+			Some((first, last)) => (first.trim(), last.trim()),
+			None => return RangeRequest::Ignored
+		};
+
+		let is_number = |value: &str| !value.is_empty() && value.chars().all(|char| char.is_ascii_digit());
+
+		let range = match (first, last) {
+			// A suffix range asks for the last n bytes
+			("", suffix_length) if is_number(suffix_length) => {
+				let suffix_length = suffix_length.parse::<u64>().unwrap_or(u64::MAX);
+
+				match suffix_length > 0 && complete_length > 0 {
+					true => Some(ByteRange {
+						first: complete_length.saturating_sub(suffix_length),
+						last: complete_length - 1
+					}),
+					false => Option::None
+				}
+			},
+			(first, last) if is_number(first) && (last.is_empty() || is_number(last)) => {
+				// Positions too large to represent are beyond the end of any file
+				let first = first.parse::<u64>().unwrap_or(u64::MAX);
+
+				let last = match last.is_empty() {
+					true => u64::MAX,
+					false => last.parse::<u64>().unwrap_or(u64::MAX)
+				};
+
+				if last < first {
+					return RangeRequest::Ignored;
+				}
+
+				match first < complete_length {
+					true => Some(ByteRange {
+						first,
+						last: last.min(complete_length - 1)
+					}),
+					false => Option::None
+				}
+			},
+			_ => return RangeRequest::Ignored
+		};
+
+		ranges.extend(range);
+	}
+
+	if ranges.is_empty() {
+		return RangeRequest::Unsatisfiable;
+	}
+
+	ranges.sort_by_key(|range| range.first);
+
+	let mut merged: Vec<ByteRange> = Vec::new();
+
+	for range in ranges {
+		match merged.last_mut() {
+			Some(previous) if range.first <= previous.last.saturating_add(1) => previous.last = previous.last.max(range.last),
+			_ => merged.push(range)
+		};
+	}
+
+	return RangeRequest::Satisfiable(merged);
+}
+
+enum BodyPart {
+	Literal(Vec<u8>),
+	Content(ByteRange)
+}
+
+/// Produces a multipart/byteranges body (RFC 9110 section 14.6) by reading each range from the content as it is sent
+pub struct MultipartByteRanges {
+	content: Box<dyn StaticContent>,
+	parts: VecDeque<BodyPart>,
+	/// Position within the part at the front of `parts`
+	position: u64,
+	boundary: String,
+	length: u64
+}
+
+impl MultipartByteRanges {
+	pub fn new(content: Box<dyn StaticContent>, ranges: &[ByteRange], content_type: &str, complete_length: u64) -> Self {
+		// The boundary must not appear in the content. A random 128 bit value makes that vanishingly unlikely.
+		let random_state = RandomState::new();
+		let boundary = format!("{:016x}{:016x}", random_state.build_hasher().finish(), random_state.hash_one(complete_length));
+
+		let mut parts = VecDeque::new();
+
+		for range in ranges {
+			parts.push_back(BodyPart::Literal(format!(
+				"\r\n--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+				boundary,
+				content_type,
+				range.content_range(complete_length)
+			).into_bytes()));
+
+			parts.push_back(BodyPart::Content(*range));
+		}
+
+		parts.push_back(BodyPart::Literal(format!("\r\n--{}--\r\n", boundary).into_bytes()));
+
+		let length = parts
+			.iter()
+			.map(|part| match part {
+				BodyPart::Literal(bytes) => bytes.len() as u64,
+				BodyPart::Content(range) => range.length()
+			})
+			.sum();
+
+		return Self {
+			content,
+			parts,
+			position: 0,
+			boundary,
+			length
+		};
+	}
+
+	pub fn content_type(&self) -> String {
+		return format!("multipart/byteranges; boundary={}", self.boundary);
+	}
+
+	pub fn length(&self) -> u64 {
+		return self.length;
+	}
+}
+
+impl Read for MultipartByteRanges {
+	fn read(&mut self, buffer: &mut [u8]) -> IoResult<usize> {
+		let (read, part_length) = match self.parts.front() {
+			None => return Ok(0),
+			Some(BodyPart::Literal(bytes)) => {
+				let remaining = &bytes[self.position as usize..];
+				let read = remaining.len().min(buffer.len());
+
+				buffer[..read].copy_from_slice(&remaining[..read]);
+
+				(read, bytes.len() as u64)
+			},
+			Some(BodyPart::Content(range)) => {
+				if self.position == 0 {
+					self.content.seek(SeekFrom::Start(range.first))?;
+				}
+
+				let remaining = range.length() - self.position;
+				let limit = (buffer.len() as u64).min(remaining) as usize;
+				let read = self.content.read(&mut buffer[..limit])?;
+
+				// The content shrinking underneath us leaves no way to finish the response correctly
+				if read == 0 && limit > 0 {
+					return Err(IoError::from(ErrorKind::UnexpectedEof));
+				}
+
+				(read, range.length())
+			}
+		};
+
+		self.position += read as u64;
+
+		if self.position >= part_length {
+			self.parts.pop_front();
+			self.position = 0;
+		}
+
+		return Ok(read);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		parse_range_header,
+		ByteRange,
+		RangeRequest,
+		MAX_RANGES,
+	};
+
+	fn satisfiable(ranges: &[(u64, u64)]) -> RangeRequest {
+		return RangeRequest::Satisfiable(ranges.iter().map(|(first, last)| ByteRange { first: *first, last: *last }).collect());
+	}
+
+	#[test]
+	fn parses_single_ranges() {
+		assert_eq!(parse_range_header("bytes=0-99", 1000), satisfiable(&[(0, 99)]));
+		assert_eq!(parse_range_header("bytes=500-", 1000), satisfiable(&[(500, 999)]));
+		assert_eq!(parse_range_header("bytes=-200", 1000), satisfiable(&[(800, 999)]));
+		assert_eq!(parse_range_header(" Bytes = 1 - 1 ", 1000), satisfiable(&[(1, 1)]));
+	}
+
+	#[test]
+	fn clamps_ranges_to_the_end_of_the_file() {
+		assert_eq!(parse_range_header("bytes=900-2000", 1000), satisfiable(&[(900, 999)]));
+		assert_eq!(parse_range_header("bytes=-2000", 1000), satisfiable(&[(0, 999)]));
+		assert_eq!(parse_range_header("bytes=0-99999999999999999999999", 1000), satisfiable(&[(0, 999)]));
+		assert_eq!(parse_range_header("bytes=-99999999999999999999999", 1000), satisfiable(&[(0, 999)]));
+	}
+
+	#[test]
+	fn ranges_outside_the_file_are_unsatisfiable() {
+		assert_eq!(parse_range_header("bytes=1000-", 1000), RangeRequest::Unsatisfiable);
+		assert_eq!(parse_range_header("bytes=99999999999999999999999-", 1000), RangeRequest::Unsatisfiable);
+		assert_eq!(parse_range_header("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+		assert_eq!(parse_range_header("bytes=0-", 0), RangeRequest::Unsatisfiable);
+		assert_eq!(parse_range_header("bytes=-1", 0), RangeRequest::Unsatisfiable);
+		assert_eq!(parse_range_header("bytes=1000-1001,2000-", 1000), RangeRequest::Unsatisfiable);
+	}
+
+	#[test]
+	fn unsatisfiable_ranges_are_dropped_from_a_set() {
+		assert_eq!(parse_range_header("bytes=2000-3000,0-9", 1000), satisfiable(&[(0, 9)]));
+		assert_eq!(parse_range_header("bytes=-0,990-", 1000), satisfiable(&[(990, 999)]));
+	}
+
+	#[test]
+	fn sorts_and_merges_overlapping_and_adjacent_ranges() {
+		assert_eq!(parse_range_header("bytes=500-599,0-99", 1000), satisfiable(&[(0, 99), (500, 599)]));
+		assert_eq!(parse_range_header("bytes=0-99,50-149", 1000), satisfiable(&[(0, 149)]));
+		assert_eq!(parse_range_header("bytes=100-199,0-99", 1000), satisfiable(&[(0, 199)]));
+		assert_eq!(parse_range_header("bytes=0-499,10-20", 1000), satisfiable(&[(0, 499)]));
+		assert_eq!(parse_range_header("bytes=0-0,2-2,1-1", 1000), satisfiable(&[(0, 2)]));
+		assert_eq!(parse_range_header("bytes=0-1,-1,500-", 1000), satisfiable(&[(0, 1), (500, 999)]));
+	}
+
+	#[test]
+	fn ignores_malformed_headers() {
+		for range in [
+			"",
+			"0-99",
+			"items=0-99",
+			"bytes=",
+			"bytes=,",
+			"bytes=-",
+			"bytes=99",
+			"bytes=a-b",
+			"bytes=+1-2",
+			"bytes=1-+2",
+			"bytes=--1",
+			"bytes=1-2-3",
+			"bytes=0-99,x"
+		] {
+			assert_eq!(parse_range_header(range, 1000), RangeRequest::Ignored, "{:?}", range);
+		}
+	}
+
+	#[test]
+	fn ignores_backwards_ranges_even_beside_valid_ones() {
+		assert_eq!(parse_range_header("bytes=99-0", 1000), RangeRequest::Ignored);
+		assert_eq!(parse_range_header("bytes=0-9,99-0", 1000), RangeRequest::Ignored);
+	}
+
+	#[test]
+	fn ignores_too_many_ranges() {
+		let ranges = |count: usize| (0..count).map(|index| format!("{}-{}", index * 2, index * 2)).collect::<Vec<String>>().join(",");
+
+		assert!(matches!(parse_range_header(&format!("bytes={}", ranges(MAX_RANGES)), 1000), RangeRequest::Satisfiable(_)));
+		assert_eq!(parse_range_header(&format!("bytes={}", ranges(MAX_RANGES + 1)), 1000), RangeRequest::Ignored);
+	}
+}
@@ -0,0 +1,124 @@
+use std::{
+	ffi::OsString,
+	fs,
+	io::Result as IoResult,
+	path::{
+		Path,
+		PathBuf
+	},
+};
+
+use crate::{
+	HttpRequest,
+	HttpResponse,
+};
+
+use super::{
+	directory_listing,
+	path::{
+		StaticPath,
+		StaticPathError
+	},
+	static_mount::{
+		OpenedFile,
+		StaticEntry,
+		StaticSource
+	},
+	StaticOptions,
+};
+
+/// A directory on disk whose files are served as-is below a URL prefix
+pub struct StaticDirectory {
+	url_prefix: Vec<String>,
+	root: PathBuf,
+	options: StaticOptions
+}
+
+impl StaticDirectory {
+	pub fn new(url_prefix: &str, root: PathBuf, options: StaticOptions) -> Self {
+		return Self {
+			url_prefix: StaticPath::prefix_segments(url_prefix),
+			root,
+			options
+		};
+	}
+
+	/// Canonicalizes a path which must be a file inside the directory, so that symlinks can't lead outside of it
+	fn contained_file(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+		let root = fs::canonicalize(&self.root).ok()?;
+		let path = fs::canonicalize(path).ok()?;
+
+		return match path.starts_with(&root) && path.is_file() {
+			true => Some(path),
+			false => Option::None
+		};
+	}
+}
+
+impl StaticSource for StaticDirectory {
+	type File = PathBuf;
+	type Directory = PathBuf;
+
+	const LISTS_DIRECTORIES: bool = true;
+
+	fn url_prefix(&self) -> &[String] {
+		return &self.url_prefix;
+	}
+
+	fn options(&self) -> &StaticOptions {
+		return &self.options;
+	}
+
+	fn find(&self, path: &StaticPath) -> Result<StaticEntry<PathBuf, PathBuf>, StaticPathError> {
+		let path = path.resolve(&self.root)?;
+
+		if path.is_file() {
+			return Ok(StaticEntry::File(path));
+		}
+
+		return match path.is_dir() {
+			true => Ok(StaticEntry::Directory(path)),
+			false => Err(StaticPathError::NotFound)
+		};
+	}
+
+	fn find_child(&self, directory: &PathBuf, name: &str) -> Option<PathBuf> {
+		return self.contained_file(directory.join(name));
+	}
+
+	fn find_relative(&self, relative_path: &str) -> Option<PathBuf> {
+		let path = StaticPath::resolve_relative(&self.root, relative_path).ok()?;
+
+		return match path.is_file() {
+			true => Some(path),
+			false => Option::None
+		};
+	}
+
+	fn find_sibling(&self, file: &PathBuf, suffix: &str) -> Option<PathBuf> {
+		let mut sibling = OsString::from(file);
+
+		sibling.push(suffix);
+
+		return self.contained_file(sibling);
+	}
+
+	fn file_name(&self, file: &PathBuf) -> String {
+		return file.to_string_lossy().into_owned();
+	}
+
+	fn open(&self, file: &PathBuf) -> IoResult<OpenedFile> {
+		let file = fs::File::open(file)?;
+		let metadata = file.metadata()?;
+
+		return Ok(OpenedFile {
+			content: Box::new(file),
+			length: metadata.len(),
+			modified: metadata.modified().ok()
+		});
+	}
+
+	fn list(&self, request: &HttpRequest, directory: &PathBuf, display_path: &str, show_dotfiles: bool) -> HttpResponse {
+		return directory_listing::render(directory, display_path, show_dotfiles, request);
+	}
+}
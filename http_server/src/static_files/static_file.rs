@@ -0,0 +1,142 @@
+use std::{
+	io::{
+		Read,
+		Seek,
+		SeekFrom
+	},
+	time::{
+		SystemTime,
+		UNIX_EPOCH
+	},
+};
+
+use crate::{
+	utils::HttpDate,
+	HttpMethod,
+	HttpRequest,
+	HttpResponse,
+};
+
+use super::{
+	conditional,
+	range::{
+		self,
+		MultipartByteRanges,
+		RangeRequest
+	},
+	EntityTagMode,
+	StaticOptions,
+};
+
+/// Content codings which may be stored alongside a file, with the extension appended to the file's name, in order of
+/// preference when the client accepts several equally
+pub const PRECOMPRESSED_ENCODINGS: &[(&str, &str)] = &[
+	("br", "br"),
+	("gzip", "gz"),
+];
+
+pub trait StaticContent: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> StaticContent for T {}
+
+/// A file about to be served, independent of where its content is stored
+pub struct StaticFile {
+	pub content: Box<dyn StaticContent>,
+	pub length: u64,
+	pub modified: Option<SystemTime>,
+	/// Content type of the file once decoded, even if it is sent compressed
+	pub content_type: String,
+	pub content_encoding: Option<String>,
+	/// Whether other encodings of the file are available, so caches need to take Accept-Encoding into account
+	pub has_encoded_variants: bool
+}
+
+impl StaticFile {
+	/// Builds the response for the file, answering conditional requests with 304 Not Modified where possible and range
+	/// requests with 206 Partial Content
+	pub fn respond(self, request: &HttpRequest, options: &StaticOptions) -> HttpResponse {
+		let etag = self.entity_tag(options.etag);
+
+		let mut response = HttpResponse::new().header(String::from("Accept-Ranges"), String::from("bytes"));
+
+		if let Some(etag) = &etag {
+			response = response.header(String::from("ETag"), etag.clone());
+		}
+
+		if let Some(modified) = self.modified {
+			response = response.header(String::from("Last-Modified"), HttpDate::format(modified));
+		}
+
+		if self.has_encoded_variants {
+			response = response.header(String::from("Vary"), String::from("Accept-Encoding"));
+		}
+
+		if let Some(content_encoding) = &self.content_encoding {
+			response = response.header(String::from("Content-Encoding"), content_encoding.clone());
+		}
+
+		if let Some(cache_control) = &options.cache_control {
+			response = response.header(String::from("Cache-Control"), cache_control.clone());
+		}
+
+		if conditional::is_not_modified(request, etag.as_deref(), self.modified) {
+			return response.status(304);
+		}
+
+		// Range requests are only defined for GET (RFC 9110 section 14.2)
+		let range_request = match request.header("Range") {
+			Some(_range) if request.method != HttpMethod::GET => RangeRequest::Ignored,
+			Some(range) if conditional::is_range_current(request, etag.as_deref(), self.modified) => {
+				range::parse_range_header(&range.to_string(), self.length)
+			},
+			_ => RangeRequest::Ignored
+		};
+
+		return match range_request {
+			RangeRequest::Ignored => response
+				.status(200)
+				.header(String::from("Content-Type"), self.content_type)
+				.stream_with_length(self.content, self.length),
+			RangeRequest::Unsatisfiable => response
+				.status(416)
+				.header(String::from("Content-Range"), format!("bytes */{}", self.length)),
+			RangeRequest::Satisfiable(ranges) => self.respond_with_ranges(response, &ranges)
+		};
+	}
+
+	fn respond_with_ranges(mut self, response: HttpResponse, ranges: &[range::ByteRange]) -> HttpResponse {
+		let response = response.status(206);
+
+		if let [range] = ranges {
+			if self.content.seek(SeekFrom::Start(range.first)).is_err() {
+				return HttpResponse::new().status(500);
+			}
+
+			return response
+				.header(String::from("Content-Type"), self.content_type)
+				.header(String::from("Content-Range"), range.content_range(self.length))
+				.stream_with_length(self.content, range.length());
+		}
+
+		let body = MultipartByteRanges::new(self.content, ranges, &self.content_type, self.length);
+		let content_type = body.content_type();
+		let length = body.length();
+
+		return response
+			.header(String::from("Content-Type"), content_type)
+			.stream_with_length(body, length);
+	}
+
+	/// Derives an entity tag from the file's size and modification time, which change whenever its content does
+	fn entity_tag(&self, mode: EntityTagMode) -> Option<String> {
+		let modified = self.modified?.duration_since(UNIX_EPOCH).ok()?;
+
+		let opaque_tag = format!("\"{:x}-{:x}\"", self.length, modified.as_nanos());
+
+		return match mode {
+			EntityTagMode::Strong => Some(opaque_tag),
+			EntityTagMode::Weak => Some(format!("W/{}", opaque_tag)),
+			EntityTagMode::Disabled => Option::None
+		};
+	}
+}
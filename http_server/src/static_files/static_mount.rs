@@ -0,0 +1,282 @@
+use std::{
+	io::{
+		Read,
+		Result as IoResult,
+		Seek,
+		SeekFrom
+	},
+	time::SystemTime,
+};
+
+use crate::{
+	mime_type::{
+		MimeType,
+		MimeTypeOverrides,
+		DEFAULT_MIME_TYPE
+	},
+	HttpRequest,
+	HttpResponse,
+};
+
+use super::{
+	negotiation,
+	path::{
+		StaticPath,
+		StaticPathError
+	},
+	static_file::{
+		self,
+		StaticContent,
+		StaticFile
+	},
+	DotfilePolicy,
+	StaticOptions,
+};
+
+/// Somewhere static files are served from below a URL prefix
+pub trait StaticMount: Send + Sync {
+	/// Number of segments in the URL prefix, so that more specific mounts can be tried first
+	fn prefix_length(&self) -> usize;
+
+	/// Whether `respond` would answer a GET request for the path, without opening or reading anything
+	fn serves(&self, path: &str) -> bool;
+
+	/// Responds with the file the request refers to, or `None` if this mount doesn't contain it so that the request
+	/// can be handled by something else
+	fn respond(&self, request: &HttpRequest, mime_types: &MimeTypeOverrides) -> Option<HttpResponse>;
+
+	/// Serves the fallback file for a page below this mount's prefix which no mount contains, so that a single-page
+	/// application can route it on the client
+	fn respond_with_fallback(&self, request: &HttpRequest, mime_types: &MimeTypeOverrides) -> Option<HttpResponse>;
+}
+
+/// What a path inside a static source refers to
+pub enum StaticEntry<F, D> {
+	File(F),
+	Directory(D)
+}
+
+/// A file's content ready to be read, along with what is known about it
+pub struct OpenedFile {
+	pub content: Box<dyn StaticContent>,
+	pub length: u64,
+	pub modified: Option<SystemTime>
+}
+
+/// Where a mount's files are looked up, e.g. a directory on disk or files embedded into the binary. Everything else about
+/// serving them (path normalization, dotfiles, index files, redirects, fallbacks, MIME types, precompressed variants
+/// and the conditional and range handling of `StaticFile`) is shared by every source through `StaticMount`.
+pub trait StaticSource: Send + Sync {
+	type File;
+	type Directory;
+
+	/// Whether `list` can list directories when the options ask for it
+	const LISTS_DIRECTORIES: bool;
+
+	fn url_prefix(&self) -> &[String];
+
+	fn options(&self) -> &StaticOptions;
+
+	/// Finds the file or directory a normalized path below the URL prefix refers to
+	fn find(&self, path: &StaticPath) -> Result<StaticEntry<Self::File, Self::Directory>, StaticPathError>;
+
+	/// Finds a file directly inside a directory, e.g. an index file
+	fn find_child(&self, directory: &Self::Directory, name: &str) -> Option<Self::File>;
+
+	/// Finds a file from a path relative to the source's root given in the server's configuration
+	fn find_relative(&self, relative_path: &str) -> Option<Self::File>;
+
+	/// Finds the file with a suffix appended to another file's name, e.g. `app.js.br` for `app.js`
+	fn find_sibling(&self, file: &Self::File, suffix: &str) -> Option<Self::File>;
+
+	/// Name of the file to look its MIME type up by
+	fn file_name(&self, file: &Self::File) -> String;
+
+	fn open(&self, file: &Self::File) -> IoResult<OpenedFile>;
+
+	/// Lists a directory's contents, only called if `LISTS_DIRECTORIES` is set
+	fn list(&self, request: &HttpRequest, directory: &Self::Directory, display_path: &str, show_dotfiles: bool) -> HttpResponse;
+}
+
+impl<T: StaticSource> StaticMount for T {
+	fn prefix_length(&self) -> usize {
+		return self.url_prefix().len();
+	}
+
+	fn serves(&self, path: &str) -> bool {
+		return match look_up(self, path) {
+			Lookup::NotServed => false,
+			Lookup::Forbidden => true,
+			Lookup::Found { entry: StaticEntry::File(_file), .. } => true,
+			Lookup::Found { entry: StaticEntry::Directory(directory), .. } => {
+				(self.options().directory_listing && T::LISTS_DIRECTORIES) || find_index_file(self, &directory).is_some()
+			}
+		};
+	}
+
+	fn respond(&self, request: &HttpRequest, mime_types: &MimeTypeOverrides) -> Option<HttpResponse> {
+		let options = self.options();
+
+		let (full_path, static_path, directory) = match look_up(self, &request.uri.path) {
+			Lookup::NotServed => return Option::None,
+			Lookup::Forbidden => return Some(HttpResponse::new().status(403)),
+			Lookup::Found { entry: StaticEntry::File(file), .. } => return Some(serve_file(self, request, &file, mime_types)),
+			Lookup::Found { full_path, static_path, entry: StaticEntry::Directory(directory) } => (full_path, static_path, directory)
+		};
+
+		let index_file = find_index_file(self, &directory);
+
+		if index_file.is_none() && !(options.directory_listing && T::LISTS_DIRECTORIES) {
+			return Option::None;
+		}
+
+		// Relative links in the index page or listing only resolve correctly if the directory's URL ends with a slash
+		if !static_path.trailing_slash {
+			let location = match &request.uri.query_string {
+				Some(query_string) => format!("{}?{}", full_path.directory_url(), query_string),
+				None => full_path.directory_url()
+			};
+
+			return Some(
+				HttpResponse::new()
+					.status(301)
+					.header(String::from("Location"), location)
+			);
+		}
+
+		if let Some(index_file) = index_file {
+			return Some(serve_file(self, request, &index_file, mime_types));
+		}
+
+		let display_path = match full_path.segments.is_empty() {
+			true => String::from("/"),
+			false => format!("/{}/", full_path.segments.join("/"))
+		};
+
+		let show_dotfiles = options.dotfiles == DotfilePolicy::Allow;
+
+		return Some(self.list(request, &directory, &display_path, show_dotfiles));
+	}
+
+	fn respond_with_fallback(&self, request: &HttpRequest, mime_types: &MimeTypeOverrides) -> Option<HttpResponse> {
+		let fallback_file = self.options().fallback_file.as_ref()?;
+
+		let static_path = StaticPath::parse(&request.uri.path).ok()?.strip_prefix(self.url_prefix())?;
+
+		if static_path.has_extension() || negotiation::media_type_quality(request, "text/html") <= 0.0 {
+			return Option::None;
+		}
+
+		let fallback_file = self.find_relative(fallback_file)?;
+
+		return Some(serve_file(self, request, &fallback_file, mime_types));
+	}
+}
+
+/// What a request path refers to in a source, once the path has been normalized and the dotfile policy applied
+enum Lookup<F, D> {
+	/// Outside the source's URL prefix, or nothing is there, so something else should handle the request
+	NotServed,
+	Forbidden,
+	Found {
+		full_path: StaticPath,
+		/// The path below the URL prefix
+		static_path: StaticPath,
+		entry: StaticEntry<F, D>
+	}
+}
+
+fn look_up<T: StaticSource>(source: &T, path: &str) -> Lookup<T::File, T::Directory> {
+	let full_path = match StaticPath::parse(path) {
+		Ok(full_path) => full_path,
+		Err(StaticPathError::Forbidden) => return Lookup::Forbidden,
+		Err(StaticPathError::NotFound) => return Lookup::NotServed
+	};
+
+	let static_path = match full_path.strip_prefix(source.url_prefix()) {
+		Some(static_path) => static_path,
+		None => return Lookup::NotServed
+	};
+
+	if static_path.has_dotfile() {
+		match source.options().dotfiles {
+			DotfilePolicy::Allow => (),
+			DotfilePolicy::Deny => return Lookup::Forbidden,
+			DotfilePolicy::Ignore => return Lookup::NotServed
+		};
+	}
+
+	return match source.find(&static_path) {
+		Ok(entry) => Lookup::Found { full_path, static_path, entry },
+		Err(StaticPathError::Forbidden) => Lookup::Forbidden,
+		Err(StaticPathError::NotFound) => Lookup::NotServed
+	};
+}
+
+fn find_index_file<T: StaticSource>(source: &T, directory: &T::Directory) -> Option<T::File> {
+	return source.options().index_files
+		.iter()
+		.find_map(|index_file| source.find_child(directory, index_file));
+}
+
+/// Serves a file from a source, picking the precompressed variant the client prefers if there is one
+fn serve_file<T: StaticSource>(source: &T, request: &HttpRequest, file: &T::File, mime_types: &MimeTypeOverrides) -> HttpResponse {
+	let options = source.options();
+
+	let mut opened = match source.open(file) {
+		Ok(opened) => opened,
+		Err(_e) => return HttpResponse::new().status(500)
+	};
+
+	// The content type always describes the original file, whichever encoding of it ends up being sent
+	let mut content_type = mime_types.lookup(&source.file_name(file));
+
+	if content_type.is_none() && options.sniff_content_type {
+		content_type = match sniff_content_type(&mut opened.content) {
+			Ok(content_type) => content_type,
+			Err(_e) => return HttpResponse::new().status(500)
+		};
+	}
+
+	let variants = match options.precompressed {
+		true => static_file::PRECOMPRESSED_ENCODINGS
+			.iter()
+			.filter_map(|(coding, extension)| source.find_sibling(file, &format!(".{}", extension)).map(|variant| (*coding, variant)))
+			.collect::<Vec<(&str, T::File)>>(),
+		false => Vec::new()
+	};
+
+	let preferred_coding = negotiation::preferred_content_coding(request, variants.iter().map(|(coding, _variant)| *coding));
+	let preferred_variant = variants.iter().find(|(coding, _variant)| Some(*coding) == preferred_coding);
+
+	let mut content_encoding = Option::None;
+
+	if let Some((coding, variant)) = preferred_variant {
+		// The variant may have disappeared since it was found, in which case the original is still fine to send
+		if let Ok(opened_variant) = source.open(variant) {
+			opened = opened_variant;
+			content_encoding = Some(String::from(*coding));
+		}
+	}
+
+	let static_file = StaticFile {
+		content: opened.content,
+		length: opened.length,
+		modified: opened.modified,
+		content_type: content_type.unwrap_or(String::from(DEFAULT_MIME_TYPE)),
+		content_encoding,
+		has_encoded_variants: !variants.is_empty()
+	};
+
+	return static_file.respond(request, options);
+}
+
+/// Guesses the type of some content from its first few bytes, leaving it positioned back at the start
+fn sniff_content_type(content: &mut Box<dyn StaticContent>) -> IoResult<Option<String>> {
+	let mut sample = Vec::new();
+
+	content.by_ref().take(512).read_to_end(&mut sample)?;
+	content.seek(SeekFrom::Start(0))?;
+
+	return Ok(MimeType::sniff(&sample).map(String::from));
+}
@@ -0,0 +1,65 @@
+/// What to do with requests for files or directories whose name starts with a dot (e.g. `.env` or `.git/`)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DotfilePolicy {
+	/// Serve them like any other file
+	Allow,
+	/// Respond with 403 Forbidden
+	Deny,
+	/// Behave as if they don't exist
+	Ignore
+}
+
+/// What kind of entity tag to send with static files
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EntityTagMode {
+	Disabled,
+	/// The tag is only guaranteed to change when the file's content changes meaningfully, so it can't be used for
+	/// byte range requests
+	Weak,
+	/// The tag changes whenever the file's content does
+	Strong
+}
+
+pub struct StaticOptions {
+	/// Files served in place of a directory, tried in order
+	pub index_files: Vec<String>,
+
+	/// Whether directories without an index file list their contents, as HTML or as JSON for clients which prefer it
+	pub directory_listing: bool,
+
+	/// Cache-Control header sent with every file served
+	pub cache_control: Option<String>,
+
+	pub dotfiles: DotfilePolicy,
+
+	/// Whether to guess the Content-Type of files with an unknown extension from their first few bytes rather than
+	/// sending application/octet-stream
+	pub sniff_content_type: bool,
+
+	/// Entity tag sent with each file, derived from its size and modification time
+	pub etag: EntityTagMode,
+
+	/// Whether to serve a precompressed sibling of a file (e.g. `app.js.br` or `app.js.gz` for `app.js`) to clients
+	/// which accept its encoding
+	pub precompressed: bool,
+
+	/// File (relative to the directory) served in place of any missing page, for single-page applications which route
+	/// on the client. Only used for requests which accept HTML and don't look like they're for an asset, i.e. whose
+	/// last segment has no file extension, so missing scripts and images still get a 404.
+	pub fallback_file: Option<String>,
+}
+
+impl Default for StaticOptions {
+	fn default() -> Self {
+		return Self {
+			index_files: vec![String::from("index.html")],
+			directory_listing: false,
+			cache_control: Option::None,
+			dotfiles: DotfilePolicy::Ignore,
+			sniff_content_type: false,
+			etag: EntityTagMode::Strong,
+			precompressed: true,
+			fallback_file: Option::None,
+		};
+	}
+}
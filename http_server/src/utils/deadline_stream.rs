@@ -0,0 +1,51 @@
+use std::{
+    io::{Error as IoError, ErrorKind, Read, Result as IoResult},
+    net::TcpStream,
+    time::Instant,
+};
+
+/// Wraps a `TcpStream` so that reads fail once an overall deadline has passed. Socket read timeouts only bound how long
+/// a single read may wait, which a client trickling in one byte at a time can keep resetting indefinitely.
+pub struct DeadlineStream {
+    stream: TcpStream,
+    deadline: Option<Instant>,
+}
+
+impl DeadlineStream {
+    pub fn new(stream: TcpStream) -> Self {
+        return Self {
+            stream,
+            deadline: Option::None
+        };
+    }
+
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) -> () {
+        self.deadline = deadline;
+    }
+}
+
+impl Read for DeadlineStream {
+    fn read(&mut self, buffer: &mut [u8]) -> IoResult<usize> {
+        let timeout = match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+
+                // A zero read timeout is rejected by the OS rather than meaning "already expired"
+                if remaining.is_zero() {
+                    return Err(IoError::from(ErrorKind::TimedOut));
+                }
+
+                Some(remaining)
+            },
+            None => None
+        };
+
+        self.stream.set_read_timeout(timeout)?;
+
+        return match self.stream.read(buffer) {
+            // Depending on the platform an expired socket timeout is reported as either of these
+            Err(error) if error.kind() == ErrorKind::WouldBlock => Err(IoError::from(ErrorKind::TimedOut)),
+            result => result
+        };
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const DAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTH_NAMES: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub struct HttpDate {}
+
+impl HttpDate {
+    /// Formats a time as an IMF-fixdate (e.g. "Sun, 06 Nov 1994 08:49:37 GMT"), the preferred format from RFC 9110
+    /// section 5.6.7. Times before the Unix epoch are clamped to it.
+    pub fn format(time: SystemTime) -> String {
+        let seconds = time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs();
+
+        let days = seconds / SECONDS_PER_DAY;
+        let seconds_of_day = seconds % SECONDS_PER_DAY;
+
+        let (year, month, day) = Self::civil_from_days(days as i64);
+
+        // 1970-01-01 was a Thursday
+        let weekday = DAY_NAMES[((days + 3) % 7) as usize];
+
+        return format!(
+            "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+            weekday,
+            day,
+            MONTH_NAMES[(month - 1) as usize],
+            year,
+            seconds_of_day / 3600,
+            seconds_of_day / 60 % 60,
+            seconds_of_day % 60
+        );
+    }
+
+    /// Parses any of the three date formats HTTP recipients must accept (RFC 9110 section 5.6.7): IMF-fixdate
+    /// ("Sun, 06 Nov 1994 08:49:37 GMT"), the obsolete RFC 850 format ("Sunday, 06-Nov-94 08:49:37 GMT") and ANSI C's
+    /// asctime() format ("Sun Nov  6 08:49:37 1994"). The day name isn't checked against the date.
+    pub fn parse(date: &str) -> Option<SystemTime> {
+        let parts = date.split_ascii_whitespace().collect::<Vec<&str>>();
+
+        let (year, month, day, time) = match parts.as_slice() {
+            [day_name, day, month, year, time, "GMT"] if day_name.ends_with(',') => {
+                (Self::parse_digits(year)?, *month, *day, *time)
+            },
+            [day_name, date, time, "GMT"] if day_name.ends_with(',') => {
+                let mut date_parts = date.split('-');
+
+                let day = date_parts.next()?;
+                let month = date_parts.next()?;
+                let year = date_parts.next()?;
+
+                if year.len() != 2 || date_parts.next().is_some() {
+                    return None;
+                }
+
+                (Self::expand_two_digit_year(Self::parse_digits(year)?), month, day, *time)
+            },
+            [_day_name, month, day, time, year] => (Self::parse_digits(year)?, *month, *day, *time),
+            _ => return None
+        };
+
+        let month = MONTH_NAMES.iter().position(|name| *name == month)? as u32 + 1;
+        let day = Self::parse_digits::<u32>(day)?;
+
+        let time_parts = time
+            .split(':')
+            .map(|part| match part.len() == 2 {
+                true => Self::parse_digits::<u64>(part),
+                false => None
+            })
+            .collect::<Option<Vec<u64>>>()?;
+
+        let (hour, minute, second) = match time_parts.as_slice() {
+            [hour, minute, second] if *hour < 24 && *minute < 60 && *second < 61 => (*hour, *minute, *second),
+            _ => return None
+        };
+
+        if year < 1970 || day == 0 || day > Self::days_in_month(year, month) {
+            return None;
+        }
+
+        let days = Self::days_from_civil(year, month, day) as u64;
+
+        return Some(UNIX_EPOCH + Duration::from_secs(days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second));
+    }
+
+    /// Parses a number made only of digits, which `str::parse` alone doesn't ensure since it accepts a leading sign
+    fn parse_digits<T: FromStr>(text: &str) -> Option<T> {
+        return match !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit()) {
+            true => text.parse::<T>().ok(),
+            false => None
+        };
+    }
+
+    /// RFC 850 dates only carry two digits of the year. Per RFC 9110 a year which appears to be more than 50 years in
+    /// the future is taken to be in the past century instead.
+    fn expand_two_digit_year(year: i64) -> i64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs();
+        let (current_year, _month, _day) = Self::civil_from_days((now / SECONDS_PER_DAY) as i64);
+
+        let century = current_year - current_year % 100;
+
+        return match century + year > current_year + 50 {
+            true => century - 100 + year,
+            false => century + year
+        };
+    }
+
+    fn days_in_month(year: i64, month: u32) -> u32 {
+        let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+
+        return match month {
+            2 if is_leap_year => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31
+        };
+    }
+
+    /// The inverse of `civil_from_days`
+    fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let shifted_month = if month > 2 { month - 3 } else { month + 9 } as i64;
+        let day_of_year = (153 * shifted_month + 2) / 5 + day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        return era * 146097 + day_of_era - 719468;
+    }
+
+    /// Converts a number of days since the Unix epoch into a (year, month, day) date in the proleptic Gregorian
+    /// calendar, using Howard Hinnant's `civil_from_days` algorithm
+    fn civil_from_days(days: i64) -> (i64, u32, u32) {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        return (year, month, day);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::HttpDate;
+
+    /// Sun, 06 Nov 1994 08:49:37 GMT, the example date used throughout RFC 9110
+    fn example_date() -> SystemTime {
+        return UNIX_EPOCH + Duration::from_secs(784111777);
+    }
+
+    fn date(year: i64, month: u32, day: u32) -> SystemTime {
+        return UNIX_EPOCH + Duration::from_secs(HttpDate::days_from_civil(year, month, day) as u64 * super::SECONDS_PER_DAY);
+    }
+
+    #[test]
+    fn formats_imf_fixdate() {
+        assert_eq!(HttpDate::format(example_date()), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(HttpDate::format(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(HttpDate::format(date(2000, 2, 29)), "Tue, 29 Feb 2000 00:00:00 GMT");
+        assert_eq!(HttpDate::format(UNIX_EPOCH - Duration::from_secs(1)), "Thu, 01 Jan 1970 00:00:00 GMT");
+    }
+
+    #[test]
+    fn parses_all_three_formats() {
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 08:49:37 GMT"), Some(example_date()));
+        assert_eq!(HttpDate::parse("Sunday, 06-Nov-94 08:49:37 GMT"), Some(example_date()));
+        assert_eq!(HttpDate::parse("Sun Nov  6 08:49:37 1994"), Some(example_date()));
+    }
+
+    #[test]
+    fn parsing_inverts_formatting() {
+        for time in [UNIX_EPOCH, example_date(), date(2024, 12, 31), date(2100, 3, 1)] {
+            assert_eq!(HttpDate::parse(&HttpDate::format(time)), Some(time));
+        }
+    }
+
+    #[test]
+    fn expands_two_digit_years_to_within_fifty_years_from_now() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let (current_year, _month, _day) = HttpDate::civil_from_days((now / super::SECONDS_PER_DAY) as i64);
+
+        for year in 0..100 {
+            let expanded = HttpDate::expand_two_digit_year(year);
+
+            assert_eq!(expanded % 100, year);
+            assert!(expanded > current_year - 50 && expanded <= current_year + 50, "{} expanded to {}", year, expanded);
+        }
+
+        assert_eq!(HttpDate::parse("Sunday, 06-Nov-1994 08:49:37 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sunday, 06-Nov-4 08:49:37 GMT"), Option::None);
+    }
+
+    #[test]
+    fn rejects_dates_that_do_not_exist() {
+        assert_eq!(HttpDate::parse("Thu, 29 Feb 2024 00:00:00 GMT"), Some(date(2024, 2, 29)));
+        assert_eq!(HttpDate::parse("Sat, 29 Feb 2000 00:00:00 GMT"), Some(date(2000, 2, 29)));
+        assert_eq!(HttpDate::parse("Thu, 29 Feb 2100 00:00:00 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Wed, 29 Feb 2023 00:00:00 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Mon, 31 Apr 2023 00:00:00 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Mon, 00 Jan 2023 00:00:00 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Mon, 01 Jan 1969 00:00:00 GMT"), Option::None);
+    }
+
+    #[test]
+    fn rejects_malformed_times() {
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 24:00:00 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 08:60:00 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 08:49:61 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 8:49:37 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 08:49 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 08:49:+7 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, +6 Nov 1994 08:49:37 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov +1994 08:49:37 GMT"), Option::None);
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        assert_eq!(HttpDate::parse(""), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 08:49:37 UTC"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 nov 1994 08:49:37 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 1994 08:49:37 GMT extra"), Option::None);
+        assert_eq!(HttpDate::parse("Sun 06 Nov 1994 08:49:37 GMT"), Option::None);
+        assert_eq!(HttpDate::parse("1994-11-06T08:49:37Z"), Option::None);
+        assert_eq!(HttpDate::parse("Sun, 06 Nov 99999999999999999999 08:49:37 GMT"), Option::None);
+    }
+}
@@ -1,5 +1,11 @@
 mod vec_scanner;
 mod url_encoding;
+mod deadline_stream;
+mod http_date;
+mod token;
 
 pub use vec_scanner::VecScanner;
-pub use url_encoding::URLEncoding;
\ No newline at end of file
+pub use url_encoding::URLEncoding;
+pub use deadline_stream::DeadlineStream;
+pub use http_date::HttpDate;
+pub use token::is_token;
\ No newline at end of file
@@ -0,0 +1,4 @@
+/// Whether the string is a token as defined by RFC 9110 section 5.6.2, the grammar of e.g. method and field names
+pub fn is_token(string: &str) -> bool {
+	return !string.is_empty() && string.chars().all(|char| char.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(char));
+}
@@ -53,4 +53,45 @@ impl URLEncoding {
 
         return output;
     }
-}
\ No newline at end of file
+
+    /// Decodes percent escapes into raw bytes without any form specific handling (i.e. "+" is left alone), as needed for
+    /// URI paths. Returns `None` if an escape is malformed.
+    pub fn percent_decode(original_string: &str) -> Option<Vec<u8>> {
+        let bytes = original_string.as_bytes();
+        let mut output = Vec::with_capacity(bytes.len());
+        let mut index = 0;
+
+        while index < bytes.len() {
+            if bytes[index] != b'%' {
+                output.push(bytes[index]);
+                index += 1;
+
+                continue;
+            }
+
+            let escape = bytes.get(index + 1..index + 3)?;
+            let escape = core::str::from_utf8(escape).ok()?;
+
+            if !escape.chars().all(|char| char.is_ascii_hexdigit()) {
+                return None;
+            }
+
+            output.push(u8::from_str_radix(escape, 16).ok()?);
+            index += 3;
+        }
+
+        return Some(output);
+    }
+
+    /// Percent-encodes everything other than unreserved characters (RFC 3986 section 2.3) so that the result can be used
+    /// as a single URI path segment
+    pub fn encode_path_segment(segment: &str) -> String {
+        return segment
+            .bytes()
+            .map(|byte| match byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                true => (byte as char).to_string(),
+                false => format!("%{:02X}", byte)
+            })
+            .collect::<String>();
+    }
+}
@@ -5,7 +5,7 @@ pub struct VecScanner<T> {
     input: Vec<T>,
 }
 
-impl<T> VecScanner<T> where T: Eq + ?Sized + Copy,
+impl<T> VecScanner<T> where T: Eq + Copy,
 {
     pub fn new(input: Vec<T>) -> Self {
         return Self {
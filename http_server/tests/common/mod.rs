@@ -0,0 +1,74 @@
+// Each test binary only uses some of these helpers
+#![allow(dead_code)]
+
+use std::{
+	env,
+	fs,
+	io::{
+		Read,
+		Write
+	},
+	net::{
+		SocketAddr,
+		TcpStream
+	},
+	path::PathBuf,
+	process,
+	thread,
+	time::Duration,
+};
+
+use http_server::HttpServer;
+
+/// Runs the server on a free port for as long as `client` takes, shutting it down again afterwards
+pub fn serve<S: Send + Sync + 'static, T>(server: &HttpServer<S>, client: impl FnOnce(SocketAddr) -> T) -> T {
+	let shutdown = server.shutdown_handle();
+	let bound = server.bind("127.0.0.1:0").unwrap();
+	let address = bound.local_addr();
+
+	return thread::scope(|scope| {
+		scope.spawn(|| bound.listen().unwrap());
+
+		let result = client(address);
+
+		shutdown.shutdown(Duration::ZERO);
+
+		return result;
+	});
+}
+
+pub fn connect(address: SocketAddr) -> TcpStream {
+	let stream = TcpStream::connect(address).unwrap();
+
+	stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+	return stream;
+}
+
+/// Sends raw bytes on a new connection and returns everything the server writes back before closing it
+pub fn send(address: SocketAddr, request: &[u8]) -> String {
+	let mut stream = connect(address);
+
+	stream.write_all(request).unwrap();
+
+	return read_to_close(&mut stream);
+}
+
+pub fn read_to_close(stream: &mut TcpStream) -> String {
+	let mut response = Vec::new();
+
+	// A reset after the server has responded still leaves the response to check
+	let _ = stream.read_to_end(&mut response);
+
+	return String::from_utf8_lossy(&response).into_owned();
+}
+
+/// A fresh directory for a test's files, unique to the test process
+pub fn temporary_directory(name: &str) -> PathBuf {
+	let directory = env::temp_dir().join(format!("http_server_test_{}_{}", process::id(), name));
+
+	let _ = fs::remove_dir_all(&directory);
+	fs::create_dir_all(&directory).unwrap();
+
+	return directory;
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use std::{
+	io::{
+		Read,
+		Write
+	},
+	net::TcpStream,
+	thread,
+	time::{
+		Duration,
+		Instant
+	},
+};
+
+use http_server::{HttpResponse, HttpServer, HttpServerOptions};
+
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_millis(300);
+const SLOW_RESPONSE_DELAY: Duration = Duration::from_millis(500);
+
+fn server(options: HttpServerOptions) -> HttpServer {
+	let mut server = HttpServer::with_options(options);
+
+	server.get(String::from("/hello"), |_request| HttpResponse::new().text("hello"));
+	server.get(String::from("/slow"), |_request| {
+		thread::sleep(SLOW_RESPONSE_DELAY);
+
+		return HttpResponse::new().text("slow");
+	});
+
+	return server;
+}
+
+fn options() -> HttpServerOptions {
+	return HttpServerOptions {
+		keep_alive_timeout: KEEP_ALIVE_TIMEOUT,
+		..HttpServerOptions::default()
+	};
+}
+
+/// The start of each response in everything a connection received
+fn responses(received: &str) -> Vec<&str> {
+	return received.split("HTTP/1.1 ").skip(1).collect();
+}
+
+#[test]
+fn pipelined_requests_are_answered_in_order_until_the_client_asks_to_close() {
+	let (received, elapsed) = common::serve(&server(options()), |address| {
+		let started = Instant::now();
+		let received = common::send(
+			address,
+			b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\nGET /slow HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\nGET /hello HTTP/1.1\r\nHost: x\r\n\r\n"
+		);
+
+		return (received, started.elapsed());
+	});
+
+	let responses = responses(&received);
+
+	assert_eq!(responses.len(), 2, "{received}");
+	assert!(responses[0].starts_with("200 ") && responses[0].ends_with("hello"), "{received}");
+	assert!(responses[0].contains("Keep-Alive: "), "{received}");
+	assert!(!responses[0].contains("Connection: close"), "{received}");
+	assert!(responses[1].starts_with("200 ") && responses[1].ends_with("slow"), "{received}");
+	assert!(responses[1].contains("Connection: close\r\n"), "{received}");
+
+	// The connection is closed as soon as the response has been sent rather than when it times out
+	assert!(elapsed < SLOW_RESPONSE_DELAY + KEEP_ALIVE_TIMEOUT, "{elapsed:?}");
+}
+
+#[test]
+fn idle_connections_are_closed_after_the_keep_alive_timeout() {
+	let (received, idle) = common::serve(&server(options()), |address| {
+		let mut stream = common::connect(address);
+
+		stream.write_all(b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+
+		// Wait for the whole response so that the time is measured from when the connection became idle
+		let mut received = Vec::new();
+		let mut buffer = [0; 1024];
+
+		while !received.ends_with(b"hello") {
+			let read = stream.read(&mut buffer).unwrap();
+
+			assert!(read > 0, "closed before the response was complete");
+			received.extend_from_slice(&buffer[..read]);
+		}
+
+		let idle_since = Instant::now();
+		let rest = common::read_to_close(&mut stream);
+
+		return (String::from_utf8_lossy(&received).into_owned() + &rest, idle_since.elapsed());
+	});
+
+	assert_eq!(responses(&received).len(), 1, "{received}");
+	assert!(idle >= KEEP_ALIVE_TIMEOUT - Duration::from_millis(50), "{idle:?}");
+	assert!(idle < Duration::from_secs(3), "{idle:?}");
+}
+
+#[test]
+fn connections_are_closed_after_the_maximum_number_of_requests() {
+	let server = server(HttpServerOptions {
+		max_requests_per_connection: 2,
+		..options()
+	});
+
+	let received = common::serve(&server, |address| {
+		return common::send(address, &b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\n".repeat(3));
+	});
+
+	let responses = responses(&received);
+
+	assert_eq!(responses.len(), 2, "{received}");
+	assert!(responses[0].contains("Keep-Alive: timeout=0, max=1\r\n"), "{received}");
+	assert!(responses[1].contains("Connection: close\r\n"), "{received}");
+}
+
+#[test]
+fn a_single_request_per_connection_disables_keep_alive() {
+	let server = server(HttpServerOptions {
+		max_requests_per_connection: 1,
+		..options()
+	});
+
+	let received = common::serve(&server, |address| {
+		return common::send(address, &b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\n".repeat(2));
+	});
+
+	let responses = responses(&received);
+
+	assert_eq!(responses.len(), 1, "{received}");
+	assert!(responses[0].contains("Connection: close\r\n"), "{received}");
+}
+
+#[test]
+fn shutdown_lets_in_flight_requests_finish_and_then_returns_from_listen() {
+	let server = server(options());
+	let shutdown = server.shutdown_handle();
+	let bound = server.bind("127.0.0.1:0").unwrap();
+	let address = bound.local_addr();
+
+	let (received, idle_received, listen_duration) = thread::scope(|scope| {
+		let listener = scope.spawn(|| {
+			let started = Instant::now();
+
+			bound.listen().unwrap();
+
+			return started.elapsed();
+		});
+
+		// An idle persistent connection has nothing in flight and shouldn't hold up shutdown
+		let mut idle = common::connect(address);
+		idle.write_all(b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+
+		let mut slow = common::connect(address);
+		slow.write_all(b"GET /slow HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+
+		// Give the slow request time to reach its route before shutting down
+		thread::sleep(SLOW_RESPONSE_DELAY / 5);
+		shutdown.shutdown(Duration::from_secs(10));
+
+		let received = common::read_to_close(&mut slow);
+		let idle_received = common::read_to_close(&mut idle);
+
+		return (received, idle_received, listener.join().unwrap());
+	});
+
+	assert!(received.starts_with("HTTP/1.1 200 ") && received.ends_with("slow"), "{received}");
+	assert!(received.contains("Connection: close\r\n"), "{received}");
+	assert_eq!(responses(&idle_received).len(), 1, "{idle_received}");
+
+	// `listen` returned once the slow request was done rather than waiting out the grace period
+	assert!(listen_duration < Duration::from_secs(5), "{listen_duration:?}");
+
+	assert!(TcpStream::connect(address).is_err(), "still accepting connections");
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use http_server::{HttpRequest, HttpResponse, HttpServer, HttpServerOptions};
+
+const MAX_REQUEST_LINE_LENGTH: usize = 64;
+const MAX_HEADER_SIZE: usize = 256;
+const MAX_HEADER_COUNT: usize = 8;
+const MAX_BODY_SIZE: usize = 16;
+
+/// Sends raw bytes to a fresh server with small limits and returns everything it writes back before closing the connection
+fn exchange(request: &[u8]) -> String {
+	let mut server = HttpServer::with_options(HttpServerOptions {
+		max_request_line_length: MAX_REQUEST_LINE_LENGTH,
+		max_header_size: MAX_HEADER_SIZE,
+		max_header_count: MAX_HEADER_COUNT,
+		max_body_size: MAX_BODY_SIZE,
+		..HttpServerOptions::default()
+	});
+
+	server.get(String::from("/hello"), |_request| HttpResponse::new().text("hello"));
+
+	let echo = |request: HttpRequest| {
+		let body = request.body.map(|body| body.as_buffer()).unwrap_or_default();
+
+		return HttpResponse::new().content(body);
+	};
+
+	server.post(String::from("/echo"), echo);
+	server.post(String::from("/upload"), echo).max_body_size = Some(MAX_BODY_SIZE * 4);
+	server.post(String::from("/tiny"), echo).max_body_size = Some(4);
+
+	return common::serve(&server, |address| common::send(address, request));
+}
+
+fn post(path: &str, body: &str) -> String {
+	return exchange(format!("POST {} HTTP/1.1\r\nHost: x\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", path, body.len(), body).as_bytes());
+}
+
+fn post_chunked(path: &str, body: &str) -> String {
+	return exchange(
+		format!("POST {} HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n", path, body.len(), body).as_bytes()
+	);
+}
+
+#[test]
+fn request_lines_up_to_the_limit_are_accepted() {
+	// The limit includes the CRLF ending the line
+	let padding = "?".repeat(MAX_REQUEST_LINE_LENGTH - "GET /hello HTTP/1.1\r\n".len());
+	let response = exchange(format!("GET /hello{} HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n", padding).as_bytes());
+
+	assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+}
+
+#[test]
+fn long_request_lines_are_rejected() {
+	let padding = "?".repeat(MAX_REQUEST_LINE_LENGTH);
+	let response = exchange(format!("GET /hello{} HTTP/1.1\r\nHost: x\r\n\r\n", padding).as_bytes());
+
+	assert!(response.starts_with("HTTP/1.1 414 "), "{response}");
+	assert!(response.contains("Connection: close\r\n"), "{response}");
+}
+
+#[test]
+fn too_many_headers_are_rejected() {
+	let headers = (0..MAX_HEADER_COUNT).map(|index| format!("X-{}: y\r\n", index)).collect::<String>();
+	let response = exchange(format!("GET /hello HTTP/1.1\r\nHost: x\r\n{}\r\n", headers).as_bytes());
+
+	assert!(response.starts_with("HTTP/1.1 431 "), "{response}");
+}
+
+#[test]
+fn large_headers_are_rejected() {
+	let response = exchange(format!("GET /hello HTTP/1.1\r\nHost: x\r\nX-Large: {}\r\n\r\n", "y".repeat(MAX_HEADER_SIZE)).as_bytes());
+
+	assert!(response.starts_with("HTTP/1.1 431 "), "{response}");
+}
+
+#[test]
+fn bodies_up_to_the_limit_are_accepted() {
+	let body = "b".repeat(MAX_BODY_SIZE);
+
+	for response in [post("/echo", &body), post_chunked("/echo", &body)] {
+		assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+		assert!(response.ends_with(&body), "{response}");
+	}
+}
+
+#[test]
+fn large_content_lengths_are_rejected_before_the_body_is_read() {
+	let response = exchange(b"POST /echo HTTP/1.1\r\nHost: x\r\nContent-Length: 1000000000\r\n\r\n");
+
+	assert!(response.starts_with("HTTP/1.1 413 "), "{response}");
+	assert!(post("/echo", &"b".repeat(MAX_BODY_SIZE + 1)).starts_with("HTTP/1.1 413 "));
+}
+
+#[test]
+fn large_chunked_bodies_are_rejected() {
+	// A single chunk which is too large is refused from its size line alone
+	let response = exchange(b"POST /echo HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\n\r\n100000\r\n");
+
+	assert!(response.starts_with("HTTP/1.1 413 "), "{response}");
+
+	// Small chunks still count towards the limit together
+	let chunks = "4\r\nbbbb\r\n".repeat(MAX_BODY_SIZE / 4 + 1);
+	let response = exchange(format!("POST /echo HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\n\r\n{}0\r\n\r\n", chunks).as_bytes());
+
+	assert!(response.starts_with("HTTP/1.1 413 "), "{response}");
+}
+
+#[test]
+fn routes_can_override_the_body_size_limit() {
+	let body = "b".repeat(MAX_BODY_SIZE * 2);
+
+	for response in [post("/upload", &body), post_chunked("/upload", &body)] {
+		assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+		assert!(response.ends_with(&body), "{response}");
+	}
+
+	for response in [post("/tiny", "bbbbb"), post_chunked("/tiny", "bbbbb")] {
+		assert!(response.starts_with("HTTP/1.1 413 "), "{response}");
+	}
+}
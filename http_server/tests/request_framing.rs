@@ -0,0 +1,103 @@
+mod common;
+
+use http_server::{HttpResponse, HttpServer};
+
+/// Sends raw bytes to a fresh server and returns everything it writes back before closing the connection
+fn exchange(request: &[u8]) -> String {
+	let mut server = HttpServer::new();
+
+	server.get(String::from("/hello"), |_request| HttpResponse::new().text("hello"));
+	server.post(String::from("/echo"), |request| {
+		let body = request.body.map(|body| body.as_buffer()).unwrap_or_default();
+
+		return HttpResponse::new().content(body);
+	});
+
+	return common::serve(&server, |address| common::send(address, request));
+}
+
+#[test]
+fn conflicting_content_lengths_are_rejected_without_reading_a_smuggled_request() {
+	let response = exchange(
+		b"POST /echo HTTP/1.1\r\nHost: x\r\nContent-Length: 26\r\ncontent-length: 0\r\n\r\nGET /hello HTTP/1.1\r\nHost: x\r\n\r\n"
+	);
+
+	assert!(response.starts_with("HTTP/1.1 400 "), "{response}");
+	assert_eq!(response.matches("HTTP/1.1 ").count(), 1, "{response}");
+	assert!(!response.contains("hello"), "{response}");
+}
+
+#[test]
+fn repeated_content_lengths_with_the_same_casing_are_rejected() {
+	let response = exchange(
+		b"POST /echo HTTP/1.1\r\nHost: x\r\nContent-Length: 0\r\nContent-Length: 26\r\n\r\nGET /hello HTTP/1.1\r\nHost: x\r\n\r\n"
+	);
+
+	assert!(response.starts_with("HTTP/1.1 400 "), "{response}");
+	assert_eq!(response.matches("HTTP/1.1 ").count(), 1, "{response}");
+}
+
+#[test]
+fn agreeing_content_lengths_are_accepted() {
+	let response = exchange(
+		b"POST /echo HTTP/1.1\r\nHost: x\r\nContent-Length: 5\r\nContent-Length: 5\r\nConnection: close\r\n\r\nabcde"
+	);
+
+	assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+	assert!(response.ends_with("abcde"), "{response}");
+}
+
+#[test]
+fn transfer_codings_other_than_chunked_are_not_implemented() {
+	let response = exchange(
+		b"POST /echo HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: gzip, chunked\r\n\r\n5\r\nabcde\r\n0\r\n\r\n"
+	);
+
+	assert!(response.starts_with("HTTP/1.1 501 "), "{response}");
+	assert!(!response.contains("abcde"), "{response}");
+}
+
+#[test]
+fn chunked_applied_twice_is_rejected() {
+	let response = exchange(
+		b"POST /echo HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nabcde\r\n0\r\n\r\n"
+	);
+
+	assert!(response.starts_with("HTTP/1.1 400 "), "{response}");
+}
+
+#[test]
+fn chunked_bodies_are_decoded() {
+	let response = exchange(
+		b"POST /echo HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n5\r\nabcde\r\n0\r\n\r\n"
+	);
+
+	assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+	assert!(response.ends_with("abcde"), "{response}");
+}
+
+#[test]
+fn whitespace_between_field_names_and_colons_is_rejected() {
+	for request in [
+		&b"POST /echo HTTP/1.1\r\nHost: x\r\nContent-Length : 3\r\n\r\nabcGET /hello HTTP/1.1\r\nHost: x\r\n\r\n"[..],
+		&b"POST /echo HTTP/1.1\r\nHost: x\r\nTransfer-Encoding : chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n"[..],
+		&b"POST /echo HTTP/1.1\r\nHost: x\r\nContent-Length\t: 3\r\n\r\nabc"[..],
+		&b"POST /echo HTTP/1.1\r\nHost: x\r\n Content-Length: 3\r\n\r\nabc"[..]
+	] {
+		let response = exchange(request);
+
+		assert!(response.starts_with("HTTP/1.1 400 "), "{response}");
+		assert_eq!(response.matches("HTTP/1.1 ").count(), 1, "{response}");
+	}
+}
+
+#[test]
+fn field_names_must_be_tokens() {
+	for request in [
+		&b"GET /hello HTTP/1.1\r\nHost: x\r\nX(y): z\r\n\r\n"[..],
+		&b"GET /hello HTTP/1.1\r\nHost: x\r\n: z\r\n\r\n"[..],
+		&b"GET /hello HTTP/1.1\r\nHost: x\r\nno colon\r\n\r\n"[..]
+	] {
+		assert!(exchange(request).starts_with("HTTP/1.1 400 "));
+	}
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::fs;
+
+use http_server::{HttpResponse, HttpServer, StaticOptions};
+
+#[test]
+fn fallback_page_does_not_hide_routes_for_other_methods() {
+	let directory = common::temporary_directory("fallback_routes");
+
+	fs::write(directory.join("index.html"), "<p>app</p>").unwrap();
+
+	let mut server = HttpServer::new();
+
+	server.post(String::from("/only-post"), |_request| HttpResponse::new().text("posted"));
+	server.serve_static_with_options(
+		directory.to_string_lossy().into_owned(),
+		StaticOptions {
+			fallback_file: Some(String::from("index.html")),
+			..StaticOptions::default()
+		}
+	);
+
+	let (only_post, client_route) = common::serve(&server, |address| {
+		let only_post = common::send(address, b"GET /only-post HTTP/1.1\r\nHost: x\r\nAccept: text/html\r\nConnection: close\r\n\r\n");
+		let client_route = common::send(address, b"GET /settings HTTP/1.1\r\nHost: x\r\nAccept: text/html\r\nConnection: close\r\n\r\n");
+
+		return (only_post, client_route);
+	});
+
+	fs::remove_dir_all(directory).unwrap();
+
+	assert!(only_post.starts_with("HTTP/1.1 405 "), "{only_post}");
+	assert!(only_post.contains("Allow: POST, OPTIONS\r\n"), "{only_post}");
+	assert!(!only_post.contains("<p>app</p>"), "{only_post}");
+
+	assert!(client_route.starts_with("HTTP/1.1 200 "), "{client_route}");
+	assert!(client_route.ends_with("<p>app</p>"), "{client_route}");
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use std::fs;
+
+use http_server::{HttpServer, StaticOptions};
+
+#[test]
+fn directory_listings_vary_by_accept() {
+	let directory = common::temporary_directory("listing_vary");
+
+	fs::write(directory.join("a.txt"), "a").unwrap();
+
+	let mut server = HttpServer::new();
+
+	server.serve_static_with_options(
+		directory.to_string_lossy().into_owned(),
+		StaticOptions {
+			directory_listing: true,
+			..StaticOptions::default()
+		}
+	);
+
+	let (html, json) = common::serve(&server, |address| {
+		let html = common::send(address, b"GET / HTTP/1.1\r\nHost: x\r\nAccept: text/html\r\nConnection: close\r\n\r\n");
+		let json = common::send(address, b"GET / HTTP/1.1\r\nHost: x\r\nAccept: application/json\r\nConnection: close\r\n\r\n");
+
+		return (html, json);
+	});
+
+	fs::remove_dir_all(directory).unwrap();
+
+	assert!(html.contains("Content-Type: text/html"), "{html}");
+	assert!(html.contains("Vary: Accept\r\n"), "{html}");
+	assert!(json.contains("Content-Type: application/json"), "{json}");
+	assert!(json.contains("Vary: Accept\r\n"), "{json}");
+}
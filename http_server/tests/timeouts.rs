@@ -0,0 +1,120 @@
+mod common;
+
+use std::{
+	io::{
+		self,
+		Read,
+		Write
+	},
+	thread,
+	time::{
+		Duration,
+		Instant
+	},
+};
+
+use http_server::{HttpResponse, HttpServer, HttpServerOptions};
+
+const TIMEOUT: Duration = Duration::from_millis(300);
+
+/// A large streamed response, for filling the socket buffers of a client that isn't reading
+const STREAMED_LENGTH: u64 = 64 * 1024 * 1024;
+
+fn server() -> HttpServer {
+	let mut server = HttpServer::with_options(HttpServerOptions {
+		header_read_timeout: TIMEOUT,
+		body_read_timeout: TIMEOUT,
+		write_timeout: TIMEOUT,
+		..HttpServerOptions::default()
+	});
+
+	server.get(String::from("/hello"), |_request| HttpResponse::new().text("hello"));
+	server.post(String::from("/echo"), |request| {
+		let body = request.body.map(|body| body.as_buffer()).unwrap_or_default();
+
+		return HttpResponse::new().content(body);
+	});
+	server.get(String::from("/large"), |_request| HttpResponse::new().stream(io::repeat(b'x').take(STREAMED_LENGTH)));
+
+	return server;
+}
+
+#[test]
+fn trickled_request_lines_time_out() {
+	let (response, elapsed) = common::serve(&server(), |address| {
+		let mut stream = common::connect(address);
+		let started = Instant::now();
+
+		// Every byte would reset a plain socket read timeout, so only an overall deadline for the request stops this
+		// before the whole line has been trickled in, which would take several seconds
+		for byte in b"GET /hello HTTP/1.1 and then some more of a request line" {
+			if stream.write_all(&[*byte]).is_err() {
+				break;
+			}
+
+			thread::sleep(Duration::from_millis(100));
+		}
+
+		return (common::read_to_close(&mut stream), started.elapsed());
+	});
+
+	assert!(response.starts_with("HTTP/1.1 408 "), "{response}");
+	assert!(response.contains("Connection: close\r\n"), "{response}");
+	assert!(elapsed < Duration::from_secs(3), "{elapsed:?}");
+}
+
+#[test]
+fn unfinished_headers_time_out() {
+	let response = common::serve(&server(), |address| {
+		let mut stream = common::connect(address);
+
+		stream.write_all(b"GET /hello HTTP/1.1\r\nHost: x\r\n").unwrap();
+
+		return common::read_to_close(&mut stream);
+	});
+
+	assert!(response.starts_with("HTTP/1.1 408 "), "{response}");
+}
+
+#[test]
+fn unfinished_bodies_time_out() {
+	let response = common::serve(&server(), |address| {
+		let mut stream = common::connect(address);
+
+		stream.write_all(b"POST /echo HTTP/1.1\r\nHost: x\r\nContent-Length: 10\r\n\r\nabc").unwrap();
+
+		return common::read_to_close(&mut stream);
+	});
+
+	assert!(response.starts_with("HTTP/1.1 408 "), "{response}");
+	assert!(!response.contains("abc"), "{response}");
+}
+
+#[test]
+fn connections_which_never_send_a_request_are_closed_without_a_response() {
+	let (response, elapsed) = common::serve(&server(), |address| {
+		let mut stream = common::connect(address);
+		let started = Instant::now();
+
+		return (common::read_to_close(&mut stream), started.elapsed());
+	});
+
+	assert_eq!(response, "");
+	assert!(elapsed >= TIMEOUT && elapsed < Duration::from_secs(3), "{elapsed:?}");
+}
+
+#[test]
+fn clients_which_stop_reading_are_dropped_after_the_write_timeout() {
+	let received = common::serve(&server(), |address| {
+		let mut stream = common::connect(address);
+
+		stream.write_all(b"GET /large HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+
+		// Long enough for the socket buffers to fill up and a write to block past the write timeout
+		thread::sleep(TIMEOUT * 4);
+
+		return common::read_to_close(&mut stream).len() as u64;
+	});
+
+	assert!(received < STREAMED_LENGTH, "{received}");
+}